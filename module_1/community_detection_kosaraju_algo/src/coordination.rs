@@ -0,0 +1,366 @@
+/* Coordinated-behaviour metrics for triaging suspicious communities.
+
+Coordinated networks of accounts tend to be dense, to interact back and forth,
+to keep their interactions inside the group, to form many closed triangles, and
+to reach each other in few hops. For each community this module measures:
+
+- density: the share of possible directed links between members that exist;
+- reciprocity: the share of those links that are returned;
+- internal ratio: the share of the members' interactions that stay inside;
+- clustering: the mean local clustering coefficient of the members, ignoring
+  direction;
+- average path length: the mean number of hops between members who can reach
+  each other inside the community, ignoring direction.
+
+The suspicion score is a weighted mean of density, reciprocity, internal ratio,
+clustering and compactness (1 / average path length), so it lies between 0 and
+1. Communities are ranked by it, and the ranking can be written as CSV.
+*/
+
+use crate::loader::InteractionGraph;
+use petgraph::prelude::*;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::io::Write;
+
+/// The most members a shortest-path search starts from in one community; larger
+/// communities sample this many members evenly, so the metrics stay fast.
+pub const MAX_PATH_SOURCES: usize = 100;
+
+/// The metrics of one community.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommunityMetrics {
+    /// The community's number in the assignment.
+    pub community: usize,
+    /// The members' names, sorted.
+    pub members: Vec<String>,
+    pub density: f64,
+    pub reciprocity: f64,
+    /// Interactions between two members.
+    pub internal_edges: usize,
+    /// Interactions between a member and an outsider, in either direction.
+    pub external_edges: usize,
+    pub internal_ratio: f64,
+    pub clustering: f64,
+    /// 0 if no two members can reach each other.
+    pub average_path_length: f64,
+    /// The weighted suspicion score; 0 until the communities are ranked.
+    pub suspicion: f64,
+}
+
+/// The weight of each metric in the suspicion score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuspicionWeights {
+    pub density: f64,
+    pub reciprocity: f64,
+    pub internal_ratio: f64,
+    pub clustering: f64,
+    pub compactness: f64,
+}
+
+impl Default for SuspicionWeights {
+    fn default() -> Self {
+        Self {
+            density: 1.0,
+            reciprocity: 1.0,
+            internal_ratio: 1.0,
+            clustering: 1.0,
+            compactness: 1.0,
+        }
+    }
+}
+
+impl SuspicionWeights {
+    /// Sets the weight of a metric by its name: `density`, `reciprocity`,
+    /// `internal_ratio`, `clustering` or `compactness`.
+    pub fn set(&mut self, metric: &str, weight: f64) -> Result<(), String> {
+        let field = match metric {
+            "density" => &mut self.density,
+            "reciprocity" => &mut self.reciprocity,
+            "internal_ratio" => &mut self.internal_ratio,
+            "clustering" => &mut self.clustering,
+            "compactness" => &mut self.compactness,
+            _ => {
+                return Err(format!(
+                    "unknown metric `{}`; expected density, reciprocity, internal_ratio, \
+                     clustering or compactness",
+                    metric
+                ))
+            }
+        };
+        *field = weight;
+        Ok(())
+    }
+
+    /// Computes the suspicion score of a community, between 0 and 1.
+    pub fn score(&self, metrics: &CommunityMetrics) -> f64 {
+        let compactness = if metrics.average_path_length > 0.0 {
+            1.0 / metrics.average_path_length
+        } else {
+            0.0
+        };
+        let weighted = [
+            (self.density, metrics.density),
+            (self.reciprocity, metrics.reciprocity),
+            (self.internal_ratio, metrics.internal_ratio),
+            (self.clustering, metrics.clustering),
+            (self.compactness, compactness),
+        ];
+        let total: f64 = weighted.iter().map(|(weight, _)| weight).sum();
+        if total == 0.0 {
+            return 0.0;
+        }
+        weighted
+            .iter()
+            .map(|(weight, value)| weight * value)
+            .sum::<f64>()
+            / total
+    }
+}
+
+/// Measures every community in the assignment, which gives the community of each node.
+pub fn community_metrics(graph: &InteractionGraph, assignment: &[usize]) -> Vec<CommunityMetrics> {
+    let count = assignment.iter().max().map_or(0, |&max| max + 1);
+    let mut members = vec![Vec::new(); count];
+    for node in graph.node_indices() {
+        members[assignment[node.index()]].push(node);
+    }
+
+    // Distinct directed links between members, and interaction counts.
+    let mut links: Vec<HashSet<(NodeIndex, NodeIndex)>> = vec![HashSet::new(); count];
+    let mut internal_edges = vec![0; count];
+    let mut external_edges = vec![0; count];
+    for edge in graph.edge_references() {
+        let (source, target) = (edge.source(), edge.target());
+        let (a, b) = (assignment[source.index()], assignment[target.index()]);
+        if a == b {
+            internal_edges[a] += 1;
+            if source != target {
+                links[a].insert((source, target));
+            }
+        } else {
+            external_edges[a] += 1;
+            external_edges[b] += 1;
+        }
+    }
+
+    (0..count)
+        .map(|community| {
+            let nodes = &members[community];
+            let links = &links[community];
+            let n = nodes.len() as f64;
+            let reciprocated = links
+                .iter()
+                .filter(|&&(source, target)| links.contains(&(target, source)))
+                .count();
+            let interactions = internal_edges[community] + external_edges[community];
+
+            // Neighbours inside the community, ignoring direction.
+            let mut neighbours: HashMap<NodeIndex, HashSet<NodeIndex>> =
+                nodes.iter().map(|&node| (node, HashSet::new())).collect();
+            for &(source, target) in links {
+                neighbours.get_mut(&source).unwrap().insert(target);
+                neighbours.get_mut(&target).unwrap().insert(source);
+            }
+
+            let mut names: Vec<String> = nodes.iter().map(|&node| graph[node].clone()).collect();
+            names.sort();
+            CommunityMetrics {
+                community,
+                members: names,
+                density: ratio(links.len(), n * (n - 1.0)),
+                reciprocity: ratio(reciprocated, links.len() as f64),
+                internal_edges: internal_edges[community],
+                external_edges: external_edges[community],
+                internal_ratio: ratio(internal_edges[community], interactions as f64),
+                clustering: mean_clustering(nodes, &neighbours),
+                average_path_length: average_path_length(nodes, &neighbours),
+                suspicion: 0.0,
+            }
+        })
+        .collect()
+}
+
+/// Scores the communities and sorts them, most suspicious first.
+pub fn rank(metrics: &mut [CommunityMetrics], weights: &SuspicionWeights) {
+    for community in metrics.iter_mut() {
+        community.suspicion = weights.score(community);
+    }
+    metrics.sort_by(|a, b| {
+        b.suspicion
+            .total_cmp(&a.suspicion)
+            .then(b.members.len().cmp(&a.members.len()))
+    });
+}
+
+/// Writes ranked communities of one or more datasets as CSV, with the members
+/// separated by spaces.
+pub fn write_csv<W: Write>(
+    writer: W,
+    datasets: &[(&str, &[CommunityMetrics])],
+) -> Result<(), Box<dyn Error>> {
+    let mut wtr = csv::Writer::from_writer(writer);
+    wtr.write_record([
+        "dataset",
+        "rank",
+        "community",
+        "size",
+        "suspicion",
+        "density",
+        "reciprocity",
+        "internal_edges",
+        "external_edges",
+        "internal_ratio",
+        "clustering",
+        "average_path_length",
+        "members",
+    ])?;
+
+    for (dataset, metrics) in datasets {
+        for (rank, m) in metrics.iter().enumerate() {
+            wtr.write_record([
+                dataset.to_string(),
+                (rank + 1).to_string(),
+                m.community.to_string(),
+                m.members.len().to_string(),
+                format!("{:.4}", m.suspicion),
+                format!("{:.4}", m.density),
+                format!("{:.4}", m.reciprocity),
+                m.internal_edges.to_string(),
+                m.external_edges.to_string(),
+                format!("{:.4}", m.internal_ratio),
+                format!("{:.4}", m.clustering),
+                format!("{:.4}", m.average_path_length),
+                m.members.join(" "),
+            ])?;
+        }
+    }
+
+    wtr.flush()?;
+    Ok(())
+}
+
+fn ratio(count: usize, total: f64) -> f64 {
+    if total > 0.0 {
+        count as f64 / total
+    } else {
+        0.0
+    }
+}
+
+// The mean local clustering coefficient; members with fewer than two neighbours count as 0.
+fn mean_clustering(
+    nodes: &[NodeIndex],
+    neighbours: &HashMap<NodeIndex, HashSet<NodeIndex>>,
+) -> f64 {
+    if nodes.is_empty() {
+        return 0.0;
+    }
+    let total: f64 = nodes
+        .iter()
+        .map(|node| {
+            let adjacent: Vec<&NodeIndex> = neighbours[node].iter().collect();
+            let k = adjacent.len();
+            if k < 2 {
+                return 0.0;
+            }
+            let mut triangles = 0;
+            for (i, a) in adjacent.iter().enumerate() {
+                for b in &adjacent[i + 1..] {
+                    if neighbours[a].contains(b) {
+                        triangles += 1;
+                    }
+                }
+            }
+            2.0 * triangles as f64 / (k * (k - 1)) as f64
+        })
+        .sum();
+    total / nodes.len() as f64
+}
+
+// The mean hop count between members that can reach each other, found by
+// breadth-first search from up to `MAX_PATH_SOURCES` evenly spaced members.
+fn average_path_length(
+    nodes: &[NodeIndex],
+    neighbours: &HashMap<NodeIndex, HashSet<NodeIndex>>,
+) -> f64 {
+    let step = nodes.len().div_ceil(MAX_PATH_SOURCES).max(1);
+    let mut total = 0;
+    let mut pairs = 0;
+
+    for &source in nodes.iter().step_by(step) {
+        let mut hops = HashMap::from([(source, 0)]);
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            let next = hops[&node] + 1;
+            for &neighbour in &neighbours[&node] {
+                if let Entry::Vacant(entry) = hops.entry(neighbour) {
+                    entry.insert(next);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        total += hops.values().sum::<usize>();
+        pairs += hops.len() - 1;
+    }
+
+    ratio(total, pairs as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loader::{build_graph, Interaction};
+
+    fn interaction(source: &str, target: &str) -> Interaction {
+        Interaction {
+            source: source.to_string(),
+            target: target.to_string(),
+            interaction_type: "retweet".to_string(),
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_metrics_and_ranking() {
+        // A reciprocal triangle a, b, c, and a chain x -> y -> z with one link out to a.
+        let graph = build_graph(&[
+            interaction("a", "b"),
+            interaction("b", "a"),
+            interaction("b", "c"),
+            interaction("c", "b"),
+            interaction("a", "c"),
+            interaction("c", "a"),
+            interaction("x", "y"),
+            interaction("y", "z"),
+            interaction("z", "a"),
+        ]);
+        let assignment = vec![0, 0, 0, 1, 1, 1];
+        let mut metrics = community_metrics(&graph, &assignment);
+
+        let triangle = &metrics[0];
+        assert_eq!(triangle.density, 1.0);
+        assert_eq!(triangle.reciprocity, 1.0);
+        assert_eq!((triangle.internal_edges, triangle.external_edges), (6, 1));
+        assert_eq!(triangle.clustering, 1.0);
+        assert_eq!(triangle.average_path_length, 1.0);
+
+        let chain = &metrics[1];
+        assert_eq!(chain.density, 2.0 / 6.0);
+        assert_eq!(chain.reciprocity, 0.0);
+        assert_eq!(chain.internal_ratio, 2.0 / 3.0);
+        assert_eq!(chain.clustering, 0.0);
+        assert_eq!(chain.average_path_length, 8.0 / 6.0);
+
+        rank(&mut metrics, &SuspicionWeights::default());
+        assert_eq!(metrics[0].members, vec!["a", "b", "c"]);
+        assert!((metrics[0].suspicion - (1.0 + 1.0 + 6.0 / 7.0 + 1.0 + 1.0) / 5.0).abs() < 1e-12);
+
+        let mut output = Vec::new();
+        write_csv(&mut output, &[("test", &metrics)]).unwrap();
+        let csv = String::from_utf8(output).unwrap();
+        assert!(csv.lines().nth(1).unwrap().starts_with("test,1,0,3,"));
+        assert!(csv.lines().nth(1).unwrap().ends_with(",a b c"));
+    }
+}
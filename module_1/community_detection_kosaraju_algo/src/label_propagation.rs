@@ -0,0 +1,198 @@
+/* Community detection by label propagation.
+
+Every account starts with its own label. In each iteration, every account takes
+the label carrying the most edge weight among its neighbours, so densely linked
+groups quickly agree on one label. An account keeps its label if that label is
+among the heaviest; other ties are broken at random. The run stops when no label
+changes. Each iteration is linear in the number of edges, so it scales to
+millions of accounts, though the result depends on the update order.
+
+Updates are asynchronous: an account sees the labels its neighbours took earlier
+in the same iteration. To run in parallel with rayon while keeping that, the
+graph is first coloured so that no two neighbours share a colour. Each iteration
+visits the colour classes in a random order and updates all accounts of one
+colour at once, since none of them are neighbours. Both the order and the tie
+breaks come from the seed, so the same seed always gives the same communities,
+however many threads run.
+*/
+
+use crate::modularity::{renumber, WeightedGraph};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// The default maximum number of iterations.
+pub const DEFAULT_MAX_ITERATIONS: usize = 100;
+
+// The LabelPropagation struct holds the seed for the random update order and tie
+// breaks, and the maximum number of iterations to run.
+pub struct LabelPropagation {
+    seed: u64,
+    max_iterations: usize,
+}
+
+/// The outcome of a label propagation run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelPropagationResult {
+    /// The community of each node, numbered from 0 in order of first appearance.
+    pub assignment: Vec<usize>,
+    /// The number of iterations that were run.
+    pub iterations: usize,
+    /// Whether the labels stopped changing before the iteration limit.
+    pub converged: bool,
+}
+
+impl LabelPropagation {
+    // Creates a run with the given seed and the default maximum number of iterations.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+        }
+    }
+
+    // Sets the maximum number of iterations.
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    // Propagates labels until they stop changing or the iteration limit is reached.
+    pub fn run(&self, graph: &WeightedGraph) -> LabelPropagationResult {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut classes = colour_classes(graph);
+        let mut labels: Vec<usize> = (0..graph.len()).collect();
+        let mut iterations = 0;
+        let mut converged = false;
+
+        while !converged && iterations < self.max_iterations {
+            classes.shuffle(&mut rng);
+            let round: u64 = rng.gen();
+            converged = true;
+
+            for class in &classes {
+                let updates: Vec<(usize, usize)> = class
+                    .par_iter()
+                    .filter_map(|&node| {
+                        let label = heaviest_label(graph, &labels, node, round);
+                        (label != labels[node]).then_some((node, label))
+                    })
+                    .collect();
+                if !updates.is_empty() {
+                    converged = false;
+                }
+                for (node, label) in updates {
+                    labels[node] = label;
+                }
+            }
+            iterations += 1;
+        }
+
+        renumber(&mut labels);
+        LabelPropagationResult {
+            assignment: labels,
+            iterations,
+            converged,
+        }
+    }
+}
+
+// Returns the label with the most edge weight among a node's neighbours. The node
+// keeps its own label if that is among the heaviest; other ties go to the label
+// with the smallest hash for this round.
+fn heaviest_label(graph: &WeightedGraph, labels: &[usize], node: usize, round: u64) -> usize {
+    let mut weights: HashMap<usize, f64> = HashMap::new();
+    for &(neighbour, weight) in graph.neighbours(node) {
+        *weights.entry(labels[neighbour]).or_default() += weight;
+    }
+    let Some(max) = weights.values().copied().reduce(f64::max) else {
+        return labels[node];
+    };
+    if weights.get(&labels[node]) == Some(&max) {
+        return labels[node];
+    }
+
+    weights
+        .into_iter()
+        .filter(|&(_, weight)| weight == max)
+        .map(|(label, _)| label)
+        .min_by_key(|&label| mix(round ^ mix(node as u64) ^ mix(label as u64).rotate_left(17)))
+        .unwrap()
+}
+
+// Colours the graph greedily so that no two neighbours share a colour, and
+// returns the nodes of each colour.
+fn colour_classes(graph: &WeightedGraph) -> Vec<Vec<usize>> {
+    let mut colours = vec![usize::MAX; graph.len()];
+    let mut classes: Vec<Vec<usize>> = Vec::new();
+    let mut taken = Vec::new();
+
+    for node in 0..graph.len() {
+        taken.clear();
+        taken.resize(classes.len() + 1, false);
+        for &(neighbour, _) in graph.neighbours(node) {
+            if colours[neighbour] < taken.len() {
+                taken[colours[neighbour]] = true;
+            }
+        }
+        let colour = taken.iter().position(|&taken| !taken).unwrap();
+        if colour == classes.len() {
+            classes.push(Vec::new());
+        }
+        colours[node] = colour;
+        classes[colour].push(node);
+    }
+    classes
+}
+
+// The SplitMix64 finalizer, which scrambles the bits of a number.
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two 5-cliques joined by one edge, plus a separate pair.
+    fn cliques() -> WeightedGraph {
+        let mut graph = WeightedGraph::new(12);
+        for base in [0, 5] {
+            for a in base..base + 5 {
+                for b in a + 1..base + 5 {
+                    graph.add_edge(a, b, 1.0);
+                }
+            }
+        }
+        graph.add_edge(4, 5, 1.0);
+        graph.add_edge(10, 11, 1.0);
+        graph
+    }
+
+    #[test]
+    fn test_label_propagation_finds_the_cliques() {
+        let result = LabelPropagation::new(7).run(&cliques());
+
+        assert!(result.converged);
+        assert_eq!(result.assignment, vec![0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 2, 2]);
+    }
+
+    #[test]
+    fn test_same_seed_gives_same_communities() {
+        let mut graph = WeightedGraph::new(60);
+        for a in 0..60 {
+            graph.add_edge(a, (a + 1) % 60, 1.0);
+            graph.add_edge(a, (a * 7 + 3) % 60, 1.0);
+        }
+
+        let first = LabelPropagation::new(42).run(&graph);
+        for _ in 0..5 {
+            assert_eq!(LabelPropagation::new(42).run(&graph), first);
+        }
+    }
+}
@@ -1,3 +1,22 @@
+/* Twitter/X interaction data for community detection.
+
+`TWITTER_USERNAMES` is a small built-in sample of troll accounts, where each pair
+of consecutive names is treated as one retweet. The `loader` module reads real
+interaction edge lists from CSV or JSONL files, and the `modularity` module
+detects communities with the Louvain and Leiden algorithms. For large graphs,
+`components` finds weakly connected components with union-find and
+`label_propagation` runs seeded, parallel label propagation. The `snapshot`
+module compares the communities of two snapshots of a dataset, and
+`coordination` scores communities for signs of coordinated behaviour.
+*/
+
+pub mod components;
+pub mod coordination;
+pub mod label_propagation;
+pub mod loader;
+pub mod modularity;
+pub mod snapshot;
+
 pub const TWITTER_USERNAMES: [&str; 170] = [
     // Existing users
     "blackmattersus",
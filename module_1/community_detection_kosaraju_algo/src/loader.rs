@@ -0,0 +1,174 @@
+/* Loading mention and retweet interactions from CSV or JSONL files.
+
+Each interaction is a directed edge from the account that acted to the account
+it mentioned, retweeted or replied to. CSV files have a header row:
+```text
+source,target,interaction_type,timestamp
+ten_gop,jenn_abrams,retweet,2016-10-01T12:00:00Z
+```
+JSONL files have one JSON object per line with the same fields:
+```text
+{"source": "ten_gop", "target": "jenn_abrams", "interaction_type": "retweet", "timestamp": "2016-10-01T12:00:00Z"}
+```
+The timestamp is optional. In the graph, every interaction becomes its own edge
+weighted by its interaction type, so repeated interactions are kept.
+*/
+
+use petgraph::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+/// A directed graph of accounts, with one edge per interaction weighted by its type.
+pub type InteractionGraph = DiGraph<String, String>;
+
+/// One interaction between two accounts.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Interaction {
+    /// The account that interacted.
+    pub source: String,
+    /// The account that was mentioned, retweeted or replied to.
+    pub target: String,
+    /// The kind of interaction, e.g. `mention` or `retweet`.
+    pub interaction_type: String,
+    /// When the interaction happened, as written in the file.
+    #[serde(default)]
+    pub timestamp: Option<String>,
+}
+
+/// Reads interactions from a file, as JSONL if its extension is `.jsonl` or
+/// `.ndjson` and as CSV otherwise.
+///
+/// # Example
+///
+/// ```no_run
+/// use community_detection_kosaraju_algo::loader::{build_graph, load_interactions};
+///
+/// let interactions = load_interactions("data/troll_interactions.csv").unwrap();
+/// let graph = build_graph(&interactions);
+/// assert_eq!(graph.edge_count(), interactions.len());
+/// ```
+pub fn load_interactions<P: AsRef<Path>>(path: P) -> Result<Vec<Interaction>, Box<dyn Error>> {
+    let path = path.as_ref();
+    let file = File::open(path)?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("jsonl") | Some("ndjson") => read_jsonl(BufReader::new(file)),
+        _ => read_csv(file),
+    }
+}
+
+/// Reads interactions from CSV with a `source,target,interaction_type,timestamp` header.
+pub fn read_csv<R: Read>(reader: R) -> Result<Vec<Interaction>, Box<dyn Error>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let mut interactions = Vec::new();
+
+    for (line, result) in rdr.deserialize().enumerate() {
+        let interaction: Interaction =
+            result.map_err(|e| format!("interaction record {}: {}", line + 1, e))?;
+        interactions.push(validate(interaction, line + 1)?);
+    }
+
+    Ok(interactions)
+}
+
+/// Reads interactions from JSONL, one JSON object per line. Blank lines are skipped.
+pub fn read_jsonl<R: BufRead>(reader: R) -> Result<Vec<Interaction>, Box<dyn Error>> {
+    let mut interactions = Vec::new();
+
+    for (line, text) in reader.lines().enumerate() {
+        let text = text?;
+        if text.trim().is_empty() {
+            continue;
+        }
+        let interaction: Interaction = serde_json::from_str(&text)
+            .map_err(|e| format!("interaction on line {}: {}", line + 1, e))?;
+        interactions.push(validate(interaction, line + 1)?);
+    }
+
+    Ok(interactions)
+}
+
+/// Builds the interaction graph, adding each account once and one edge per interaction.
+pub fn build_graph(interactions: &[Interaction]) -> InteractionGraph {
+    let mut graph = InteractionGraph::new();
+    let mut nodes = HashMap::new();
+
+    for interaction in interactions {
+        let mut node = |name: &str| {
+            *nodes
+                .entry(name.to_string())
+                .or_insert_with(|| graph.add_node(name.to_string()))
+        };
+        let source = node(&interaction.source);
+        let target = node(&interaction.target);
+        graph.add_edge(source, target, interaction.interaction_type.clone());
+    }
+
+    graph
+}
+
+/// Reads interactions from a file and builds their graph. See [`load_interactions`].
+pub fn load_graph<P: AsRef<Path>>(path: P) -> Result<InteractionGraph, Box<dyn Error>> {
+    Ok(build_graph(&load_interactions(path)?))
+}
+
+// Rejects interactions without both accounts or a type, and treats an empty
+// timestamp as missing.
+fn validate(mut interaction: Interaction, line: usize) -> Result<Interaction, Box<dyn Error>> {
+    if interaction.source.is_empty()
+        || interaction.target.is_empty()
+        || interaction.interaction_type.is_empty()
+    {
+        return Err(format!(
+            "interaction {} must have a source, a target and an interaction type",
+            line
+        )
+        .into());
+    }
+    if interaction.timestamp.as_deref() == Some("") {
+        interaction.timestamp = None;
+    }
+    Ok(interaction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_and_jsonl_build_the_same_graph() {
+        let csv = "source,target,interaction_type,timestamp\n\
+                   ten_gop,jenn_abrams,retweet,2016-10-01T12:00:00Z\n\
+                   jenn_abrams,ten_gop,mention,\n\
+                   ten_gop,jenn_abrams,reply,2016-10-02T08:30:00Z\n";
+        let jsonl = r#"{"source": "ten_gop", "target": "jenn_abrams", "interaction_type": "retweet", "timestamp": "2016-10-01T12:00:00Z"}
+{"source": "jenn_abrams", "target": "ten_gop", "interaction_type": "mention"}
+
+{"source": "ten_gop", "target": "jenn_abrams", "interaction_type": "reply", "timestamp": "2016-10-02T08:30:00Z"}
+"#;
+        let from_csv = read_csv(csv.as_bytes()).unwrap();
+        let from_jsonl = read_jsonl(jsonl.as_bytes()).unwrap();
+        assert_eq!(from_csv, from_jsonl);
+        assert_eq!(from_csv[1].timestamp, None);
+
+        let graph = build_graph(&from_csv);
+        assert_eq!(graph.node_count(), 2);
+        let types: Vec<&str> = graph.edge_weights().map(String::as_str).collect();
+        assert_eq!(types, vec!["retweet", "mention", "reply"]);
+    }
+
+    #[test]
+    fn test_missing_fields_are_rejected() {
+        let csv = "source,target,interaction_type,timestamp\nten_gop,,retweet,\n";
+        assert!(read_csv(csv.as_bytes()).is_err());
+
+        let jsonl = r#"{"source": "ten_gop", "target": "jenn_abrams"}"#;
+        let error = read_jsonl(jsonl.as_bytes()).unwrap_err();
+        assert!(error.to_string().contains("line 1"));
+    }
+}
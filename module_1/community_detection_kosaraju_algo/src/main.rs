@@ -3,29 +3,178 @@ Utilising Kosaraju's algorithm to detect Strongly Connected Components (SCCs).
 Kosaraju’s algorithm is a two-pass depth-first search (DFS) approach to find SCCs
 in a directed graph. An SCC is a maximal subset of a graph where every node is
 reachable from every other node in that subset.
+
+Usage:
+
+cargo run
+or
+cargo run -- data/troll_interactions.csv
+or
+cargo run -- data/troll_interactions.csv --weight retweet=2 --weight mention=0.5
+
+Without a file, the built-in sample of usernames is analysed, treating each pair of
+consecutive names as a retweet. With a file, the interactions it lists are
+analysed instead; see the `loader` module for the CSV and JSONL formats.
+
+Besides Kosaraju's SCCs, communities are detected as weakly connected components,
+by label propagation, and with the Louvain and Leiden algorithms, which maximise
+modularity. All but the SCCs ignore edge direction. Each interaction
+weighs 1 unless its type is given another weight with `--weight`, and repeated
+interactions between two accounts add up. Label propagation is seeded with
+`--seed`, so runs are reproducible.
+
+The Leiden communities of two snapshots can be compared, matching communities by
+the Jaccard overlap of their members to find which were born, died, merged,
+split or grew. Without files, the initial and expanded built-in datasets are
+compared. The full report can be written as JSON:
+
+cargo run -- before.csv --compare after.csv --report snapshot_diff.json
+
+To help triage coordinated accounts, the Leiden communities are ranked by a
+suspicion score combining their density, reciprocity, share of internal
+interactions, clustering and compactness. The weights are configurable, and the
+full ranking can be written as CSV:
+
+cargo run -- data/troll_interactions.csv --suspicion-weight reciprocity=2 --scores rankings.csv
+
+The latest graph (the expanded dataset, the interactions file or the later
+snapshot) can be exported for Graphviz or Gephi, with accounts coloured by Leiden
+community and sized by degree centrality. The format follows the extension:
+
+cargo run -- data/troll_interactions.csv --export trolls.gexf
 */
 
+use clap::Parser;
+use community_detection_kosaraju_algo::components::weakly_connected_components;
+use community_detection_kosaraju_algo::coordination::{
+    community_metrics, rank, write_csv, CommunityMetrics, SuspicionWeights,
+};
+use community_detection_kosaraju_algo::label_propagation::LabelPropagation;
+use community_detection_kosaraju_algo::loader::{self, Interaction, InteractionGraph};
+use community_detection_kosaraju_algo::modularity::{leiden, louvain, Communities, WeightedGraph};
+use community_detection_kosaraju_algo::snapshot::{
+    CommunityEvent, SnapshotDiff, DEFAULT_MATCH_THRESHOLD,
+};
 use community_detection_kosaraju_algo::TWITTER_USERNAMES;
+use graph_export::ExportGraph;
 use petgraph::algo::kosaraju_scc;
-use petgraph::prelude::*;
-use std::collections::HashMap;
+use petgraph::graph::NodeIndex;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+/// Detect communities of accounts in Twitter/X interaction data
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// CSV or JSONL file of `source,target,interaction_type,timestamp` interactions;
+    /// the built-in usernames are used when omitted
+    interactions: Option<PathBuf>,
+
+    /// The weight of an interaction type for Louvain and Leiden, as `type=weight`;
+    /// may be repeated, and unlisted types weigh 1
+    #[arg(short, long = "weight", value_parser = parse_weight)]
+    weights: Vec<(String, f64)>,
+
+    /// The seed for label propagation's random update order and tie breaks
+    #[arg(short, long, default_value = "42")]
+    seed: u64,
+
+    /// A later snapshot of the interactions to compare the communities with
+    #[arg(short, long, requires = "interactions")]
+    compare: Option<PathBuf>,
+
+    /// The Jaccard overlap at which communities of two snapshots match, above 0
+    /// and at most 1
+    #[arg(short, long, default_value_t = DEFAULT_MATCH_THRESHOLD, value_parser = parse_match_threshold)]
+    match_threshold: f64,
+
+    /// Write the snapshot comparison as JSON to this file
+    #[arg(short, long)]
+    report: Option<PathBuf>,
+
+    /// The weight of a metric in the suspicion score, as `metric=weight`; may be
+    /// repeated, and unlisted metrics weigh 1
+    #[arg(long = "suspicion-weight", value_parser = parse_suspicion_weight)]
+    suspicion_weights: Vec<(String, f64)>,
+
+    /// Write the Leiden communities, ranked by suspicion, as CSV to this file
+    #[arg(long)]
+    scores: Option<PathBuf>,
+
+    /// Export the latest graph, coloured by Leiden community, to a .dot, .graphml or
+    /// .gexf file
+    #[arg(short, long)]
+    export: Option<PathBuf>,
+}
+
+/// Parses a `name=weight` pair
+fn parse_weight(s: &str) -> Result<(String, f64), String> {
+    let (interaction_type, weight) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected `name=weight`, got `{}`", s))?;
+    let weight: f64 = weight
+        .parse()
+        .map_err(|e| format!("invalid weight `{}`: {}", weight, e))?;
+    if !weight.is_finite() || weight < 0.0 {
+        return Err(format!(
+            "weight must be a non-negative number, got {}",
+            weight
+        ));
+    }
+    Ok((interaction_type.to_string(), weight))
+}
+
+/// Parses a Jaccard match threshold, which must be above 0 and at most 1
+fn parse_match_threshold(s: &str) -> Result<f64, String> {
+    let threshold: f64 = s
+        .parse()
+        .map_err(|e| format!("invalid threshold `{}`: {}", s, e))?;
+    if !(threshold > 0.0 && threshold <= 1.0) {
+        return Err(format!(
+            "threshold must be above 0 and at most 1, got {}",
+            threshold
+        ));
+    }
+    Ok(threshold)
+}
+
+/// Parses a `metric=weight` pair for the suspicion score
+fn parse_suspicion_weight(s: &str) -> Result<(String, f64), String> {
+    let (metric, weight) = parse_weight(s)?;
+    SuspicionWeights::default().set(&metric, weight)?;
+    Ok((metric, weight))
+}
 
 /// Analyzes the graph by detecting strongly connected components (SCCs),
 /// and prints the number of communities, community sizes, and the largest community.
+/// It then compares the SCCs with weakly connected components, label propagation,
+/// Louvain and Leiden communities by their modularity and sizes, and lists the
+/// members of each Louvain and Leiden community.
+/// Returns the Leiden communities.
 ///
 /// # Arguments
 ///
-/// * `graph` - A directed graph with Twitter usernames as nodes.
+/// * `graph` - A directed graph with Twitter usernames as nodes and interactions as edges.
 /// * `description` - A label to describe the analysis (e.g., "Initial dataset", "Expanded dataset").
+/// * `weights` - The weight of each interaction type; unlisted types weigh 1.
+/// * `seed` - The seed for label propagation.
 ///
 /// # Example
 ///
 /// ```
 /// let graph = build_graph(&["user1", "user2", "user3", "user1"]);
-/// analyze_graph(&graph, "Test Dataset");
+/// analyze_graph(&graph, "Test Dataset", &HashMap::new(), 42);
 /// ```
 ///
-fn analyze_graph(graph: &DiGraph<&str, &str>, description: &str) {
+fn analyze_graph(
+    graph: &InteractionGraph,
+    description: &str,
+    weights: &HashMap<String, f64>,
+    seed: u64,
+) -> Communities {
     // Use Kosaraju's algorithm to detect SCCs
     let scc = kosaraju_scc(graph);
 
@@ -46,13 +195,211 @@ fn analyze_graph(graph: &DiGraph<&str, &str>, description: &str) {
         );
         let usernames: Vec<&str> = largest_scc
             .iter()
-            .map(|&node_index| graph[node_index])
+            .map(|&node_index| graph[node_index].as_str())
             .collect();
         println!(
             "{}: Users in the largest community: {:?}",
             description, usernames
         );
     }
+
+    // Detect modularity-based communities on the undirected, weighted graph
+    let weighted = WeightedGraph::from_digraph(graph, |interaction_type| {
+        weights.get(interaction_type).copied().unwrap_or(1.0)
+    });
+    let mut scc_assignment = vec![0; graph.node_count()];
+    for (community, component) in scc.iter().enumerate() {
+        for node_index in component {
+            scc_assignment[node_index.index()] = community;
+        }
+    }
+    let label_propagation = LabelPropagation::new(seed).run(&weighted);
+    let kosaraju = Communities::new(&weighted, scc_assignment);
+    let weak = Communities::new(&weighted, weakly_connected_components(&weighted));
+    let propagated = Communities::new(&weighted, label_propagation.assignment);
+    let louvain_communities = louvain(&weighted);
+    let leiden_communities = leiden(&weighted);
+    let partitions = [
+        ("Kosaraju SCC", &kosaraju),
+        ("Weak components", &weak),
+        ("Label propagation", &propagated),
+    ];
+    let modularity_based = [
+        ("Louvain", &louvain_communities),
+        ("Leiden", &leiden_communities),
+    ];
+
+    // Print the methods side by side
+    println!(
+        "{}: {:<17} {:>11} {:>10}  Sizes",
+        description, "Method", "Communities", "Modularity"
+    );
+    for (method, communities) in partitions.iter().chain(&modularity_based) {
+        let sizes: Vec<usize> = communities.members().iter().map(Vec::len).collect();
+        println!(
+            "{}: {:<17} {:>11} {:>10.4}  {:?}",
+            description,
+            method,
+            communities.count(),
+            communities.modularity,
+            sizes
+        );
+    }
+
+    // Print the members of each modularity-based community
+    for (method, communities) in &modularity_based {
+        for (i, members) in communities.members().iter().enumerate() {
+            let usernames: Vec<&str> = members
+                .iter()
+                .map(|&node| graph[NodeIndex::new(node)].as_str())
+                .collect();
+            println!(
+                "{}: {} community {} ({} users): {}",
+                description,
+                method,
+                i + 1,
+                members.len(),
+                usernames.join(", ")
+            );
+        }
+    }
+
+    leiden_communities
+}
+
+/// Ranks the communities by their suspicion score and prints the most suspicious.
+///
+/// # Arguments
+///
+/// * `graph` - The directed interaction graph.
+/// * `communities` - The communities to rank.
+/// * `description` - A label to describe the analysis.
+/// * `weights` - The weight of each metric in the suspicion score.
+fn rank_communities(
+    graph: &InteractionGraph,
+    communities: &Communities,
+    description: &str,
+    weights: &SuspicionWeights,
+) -> Vec<CommunityMetrics> {
+    let mut metrics = community_metrics(graph, &communities.assignment);
+    rank(&mut metrics, weights);
+
+    println!(
+        "{}: {:<4} {:>4} {:>9} {:>7} {:>11} {:>8} {:>10} {:>9}",
+        description,
+        "Rank",
+        "Size",
+        "Suspicion",
+        "Density",
+        "Reciprocity",
+        "Internal",
+        "Clustering",
+        "Path len"
+    );
+    for (i, m) in metrics.iter().take(5).enumerate() {
+        println!(
+            "{}: {:<4} {:>4} {:>9.3} {:>7.3} {:>11.3} {:>8.3} {:>10.3} {:>9.3}",
+            description,
+            i + 1,
+            m.members.len(),
+            m.suspicion,
+            m.density,
+            m.reciprocity,
+            m.internal_ratio,
+            m.clustering,
+            m.average_path_length
+        );
+    }
+    metrics
+}
+
+/// Compares the communities of two snapshots, prints the events between them,
+/// and writes the full report as JSON if a path is given.
+fn compare_snapshots(
+    before: &[CommunityMetrics],
+    after: &[CommunityMetrics],
+    args: &Args,
+) -> Result<(), Box<dyn Error>> {
+    let members = |metrics: &[CommunityMetrics]| -> Vec<Vec<String>> {
+        metrics.iter().map(|m| m.members.clone()).collect()
+    };
+    let diff = SnapshotDiff::new(&members(before), &members(after), args.match_threshold);
+    let ids = |ids: &[usize]| {
+        ids.iter()
+            .map(|id| format!("#{}", id))
+            .collect::<Vec<_>>()
+            .join(" + ")
+    };
+
+    println!(
+        "Snapshot comparison: {} communities before, {} after",
+        diff.before.len(),
+        diff.after.len()
+    );
+    for event in &diff.events {
+        match event {
+            CommunityEvent::Born { after } => {
+                println!("Born: #{} ({} users)", after, diff.after[*after].size)
+            }
+            CommunityEvent::Died { before } => {
+                println!("Died: #{} ({} users)", before, diff.before[*before].size)
+            }
+            CommunityEvent::Merged { before, after } => {
+                println!("Merged: {} into #{}", ids(before), after)
+            }
+            CommunityEvent::Split { before, after } => {
+                println!("Split: #{} into {}", before, ids(after))
+            }
+            CommunityEvent::Grew {
+                before,
+                after,
+                joined,
+                left,
+                ..
+            } => print_continuation("Grew", &diff, *before, *after, joined, left),
+            CommunityEvent::Shrank {
+                before,
+                after,
+                joined,
+                left,
+                ..
+            } => print_continuation("Shrank", &diff, *before, *after, joined, left),
+            CommunityEvent::Continued {
+                before,
+                after,
+                joined,
+                left,
+                ..
+            } => print_continuation("Continued", &diff, *before, *after, joined, left),
+        }
+    }
+
+    if let Some(path) = &args.report {
+        diff.write_json(BufWriter::new(File::create(path)?))?;
+        println!("Wrote the snapshot comparison to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Prints a community that continues from one snapshot to the next
+fn print_continuation(
+    verb: &str,
+    diff: &SnapshotDiff,
+    before: usize,
+    after: usize,
+    joined: &[String],
+    left: &[String],
+) {
+    println!(
+        "{}: #{} -> #{} ({} -> {} users, {} joined, {} left)",
+        verb,
+        before,
+        after,
+        diff.before[before].size,
+        diff.after[after].size,
+        joined.len(),
+        left.len()
+    );
 }
 
 /// Builds a directed graph from a list of Twitter usernames.
@@ -64,48 +411,36 @@ fn analyze_graph(graph: &DiGraph<&str, &str>, description: &str) {
 ///
 /// # Returns
 ///
-/// A directed graph (`DiGraph`) where each node is a Twitter username, and each edge represents an interaction.
+/// A directed graph where each node is a Twitter username, and each edge represents an interaction.
 ///
 /// # Example
 ///
 /// ```
 /// let graph = build_graph(&["user1", "user2", "user3", "user1"]);
 /// assert_eq!(graph.node_count(), 3);
-/// assert_eq!(graph.edge_count(), 2);
+/// assert_eq!(graph.edge_count(), 3);
 /// ```
+fn build_graph(usernames: &[&str]) -> InteractionGraph {
+    // Treat each pair of neighbouring names as a retweet
+    let interactions: Vec<Interaction> = usernames
+        .windows(2)
+        .map(|window| Interaction {
+            source: window[0].to_string(),
+            target: window[1].to_string(),
+            interaction_type: "retweets".to_string(),
+            timestamp: None,
+        })
+        .collect();
 
-/*'a: The lifetime parameter 'a ensures that the lifetime of references in the usernames slice,
-and the references to nodes and edges in the graph, all have the same lifetime. This guarantees
-that the data referenced by the graph is valid for as long as the usernames slice is valid,
-preventing dangling references.
-*/
-fn build_graph<'a>(usernames: &'a [&'a str]) -> DiGraph<&'a str, &'a str> {
-    // Create a new directed Graph
-    let mut graph = DiGraph::<&'a str, &'a str>::new();
-    // Create a HashMap to store node indices by user name
-    let mut nodes = HashMap::new();
-
-    // Iterate over the data to populate the graph
-    for window in usernames.windows(2) {
-        let user = window[0];
-        let mention = window[1];
-
-        // Add the nodes to the graph and to the HashMap
-        let user_node = *nodes.entry(user).or_insert_with(|| graph.add_node(user));
-        let mention_node = *nodes
-            .entry(mention)
-            .or_insert_with(|| graph.add_node(mention));
-
-        graph.add_edge(user_node, mention_node, "retweets");
-    }
-
-    graph // return the graph
+    loader::build_graph(&interactions)
 }
 
 /// Main function to run the community detection analysis on Twitter user data.
 ///
-/// This function analyzes both an initial dataset of 140 usernames and an expanded dataset of 170 usernames.
-/// It builds graphs from these datasets and compares the community structures between them.
+/// Without an interactions file, this function analyzes both an initial dataset of 140 usernames and
+/// an expanded dataset of 170 usernames, and compares the community structures between them.
+/// With a file, it analyzes the interactions listed in the file, and compares them with a later
+/// snapshot if one is given.
 ///
 /// # Example
 ///
@@ -113,12 +448,121 @@ fn build_graph<'a>(usernames: &'a [&'a str]) -> DiGraph<&'a str, &'a str> {
 /// main();
 /// ```
 ///
-fn main() {
-    // Initial dataset analysis
-    let initial_graph = build_graph(&TWITTER_USERNAMES[..140]); // Original 140 usernames
-    analyze_graph(&initial_graph, "Initial dataset");
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let mut datasets = Vec::new();
 
-    // Expanded dataset analysis
-    let expanded_graph = build_graph(&TWITTER_USERNAMES); // Expanded dataset with 200 usernames
-    analyze_graph(&expanded_graph, "Expanded dataset");
+    // The graphs to analyze, in order; the last is the one exported
+    let mut graphs = Vec::new();
+    if let Some(path) = &args.interactions {
+        graphs.push(("Interaction dataset", load(path)?));
+        if let Some(path) = &args.compare {
+            graphs.push(("Later snapshot", load(path)?));
+        } else if args.report.is_some() {
+            return Err("--report can only be used when comparing snapshots".into());
+        }
+    } else {
+        // Initial dataset of the original 140 usernames, and the expanded dataset of all 170
+        graphs.push(("Initial dataset", build_graph(&TWITTER_USERNAMES[..140])));
+        graphs.push(("Expanded dataset", build_graph(&TWITTER_USERNAMES)));
+    }
+
+    let mut leiden = Vec::new();
+    for (description, graph) in &graphs {
+        let (communities, metrics) = study(graph, description, &args);
+        leiden.push(communities);
+        datasets.push((*description, metrics));
+    }
+
+    // Compare the communities of the two datasets
+    if datasets.len() == 2 {
+        compare_snapshots(&datasets[0].1, &datasets[1].1, &args)?;
+    }
+
+    if let Some(path) = &args.scores {
+        let datasets: Vec<(&str, &[CommunityMetrics])> = datasets
+            .iter()
+            .map(|(dataset, metrics)| (*dataset, metrics.as_slice()))
+            .collect();
+        write_csv(BufWriter::new(File::create(path)?), &datasets)?;
+        println!("Wrote the community rankings to {}", path.display());
+    }
+
+    if let (Some(path), Some((_, graph)), Some(communities)) =
+        (&args.export, graphs.last(), leiden.last())
+    {
+        export_graph(graph, communities, path)?;
+        println!("Exported the graph to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Detects the communities of a graph and ranks the Leiden communities by suspicion
+fn study(
+    graph: &InteractionGraph,
+    description: &str,
+    args: &Args,
+) -> (Communities, Vec<CommunityMetrics>) {
+    let weights: HashMap<String, f64> = args.weights.iter().cloned().collect();
+    let mut suspicion = SuspicionWeights::default();
+    for (metric, weight) in &args.suspicion_weights {
+        // The metric names were checked when the arguments were parsed
+        suspicion.set(metric, *weight).unwrap();
+    }
+
+    let communities = analyze_graph(graph, description, &weights, args.seed);
+    let metrics = rank_communities(graph, &communities, description, &suspicion);
+    (communities, metrics)
+}
+
+/// Writes the graph for Graphviz or Gephi, coloured by Leiden community and sized
+/// by degree centrality: the share of other accounts each account interacted with
+fn export_graph(
+    graph: &InteractionGraph,
+    communities: &Communities,
+    path: &PathBuf,
+) -> Result<(), Box<dyn Error>> {
+    let others = graph.node_count().saturating_sub(1).max(1) as f64;
+    let centrality: Vec<f64> = graph
+        .node_indices()
+        .map(|node| {
+            let neighbours: HashSet<NodeIndex> = graph
+                .neighbors_undirected(node)
+                .filter(|&n| n != node)
+                .collect();
+            neighbours.len() as f64 / others
+        })
+        .collect();
+
+    ExportGraph::from_graph(graph)
+        .with_communities(communities.assignment.clone())?
+        .with_scores(centrality)?
+        .save(path)
+}
+
+/// Loads an interactions file and prints its size
+fn load(path: &PathBuf) -> Result<InteractionGraph, Box<dyn Error>> {
+    let graph = loader::load_graph(path)?;
+    println!(
+        "Loaded {} accounts and {} interactions from {}",
+        graph.node_count(),
+        graph.edge_count(),
+        path.display()
+    );
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_threshold_range() {
+        for valid in ["1", "0.3", "1e-9"] {
+            assert!(parse_match_threshold(valid).is_ok(), "{}", valid);
+        }
+        for invalid in ["0", "-0.1", "1.0000001", "NaN", "inf", "half"] {
+            assert!(parse_match_threshold(invalid).is_err(), "{}", invalid);
+        }
+    }
 }
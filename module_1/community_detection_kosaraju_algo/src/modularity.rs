@@ -0,0 +1,470 @@
+/* Modularity-based community detection with the Louvain and Leiden algorithms.
+
+Modularity measures how much more weight falls inside communities than would be
+expected if edges were placed at random while keeping every node's degree:
+    Q = Σ_c [ in_c / 2m - (tot_c / 2m)² ]
+where `in_c` is twice the weight of the edges inside community c, `tot_c` is the
+total degree of its nodes and `m` is the total edge weight. Direction is ignored,
+so a retweet from a to b ties a and b together just as one from b to a would.
+
+Louvain starts with every node in its own community and repeatedly moves single
+nodes to the neighbouring community that raises modularity the most. When no
+move helps, each community is collapsed into one node and the process repeats on
+the smaller graph, until nothing changes.
+
+Leiden adds a refinement step before collapsing: each community is split into
+sub-communities grown by merging well-connected nodes along edges, and those are
+collapsed instead. This guarantees every community is connected, which Louvain
+does not. Here the refinement merges greedily instead of randomly, so both
+algorithms are deterministic.
+*/
+
+use petgraph::prelude::*;
+use std::collections::HashMap;
+
+// Stops the level loop even if floating-point noise keeps producing tiny moves.
+const MAX_LEVELS: usize = 100;
+// The smallest modularity gain that counts as an improvement.
+const MIN_GAIN: f64 = 1e-12;
+
+/// An undirected weighted graph, the input to the community detection algorithms.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WeightedGraph {
+    neighbours: Vec<Vec<(usize, f64)>>,
+    self_loops: Vec<f64>,
+    degrees: Vec<f64>,
+    total_weight: f64,
+}
+
+/// A division of a graph's nodes into communities.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Communities {
+    /// The community of each node, numbered from 0 in order of first appearance.
+    pub assignment: Vec<usize>,
+    /// The modularity of the division.
+    pub modularity: f64,
+    /// The number of levels the graph was collapsed through; 0 for methods that
+    /// do not collapse it.
+    pub levels: usize,
+}
+
+impl WeightedGraph {
+    /// Creates a graph of `n` nodes without edges.
+    pub fn new(n: usize) -> Self {
+        Self {
+            neighbours: vec![Vec::new(); n],
+            self_loops: vec![0.0; n],
+            degrees: vec![0.0; n],
+            total_weight: 0.0,
+        }
+    }
+
+    /// Builds an undirected graph from a directed one, weighting each edge with
+    /// `edge_weight`. Parallel edges in either direction add up.
+    pub fn from_digraph<N, E, F>(graph: &DiGraph<N, E>, edge_weight: F) -> Self
+    where
+        F: Fn(&E) -> f64,
+    {
+        let mut weighted = Self::new(graph.node_count());
+        for edge in graph.edge_references() {
+            weighted.add_edge(
+                edge.source().index(),
+                edge.target().index(),
+                edge_weight(edge.weight()),
+            );
+        }
+        weighted
+    }
+
+    /// Adds an undirected edge. A self-loop counts twice towards its node's degree.
+    pub fn add_edge(&mut self, a: usize, b: usize, weight: f64) {
+        if a == b {
+            self.self_loops[a] += weight;
+        } else {
+            self.neighbours[a].push((b, weight));
+            self.neighbours[b].push((a, weight));
+        }
+        self.degrees[a] += weight;
+        self.degrees[b] += weight;
+        self.total_weight += weight;
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.neighbours.len()
+    }
+
+    /// Returns `true` if the graph has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.neighbours.is_empty()
+    }
+
+    /// Returns the neighbours of a node and the weight of the edge to each. A node
+    /// joined by parallel edges appears once per edge.
+    pub fn neighbours(&self, node: usize) -> &[(usize, f64)] {
+        &self.neighbours[node]
+    }
+
+    /// Returns the total edge weight.
+    pub fn total_weight(&self) -> f64 {
+        self.total_weight
+    }
+
+    // Collapses each group into one node, summing the weights between groups and
+    // turning the weight inside a group into a self-loop.
+    fn aggregate(&self, groups: &[usize], group_count: usize) -> Self {
+        let mut collapsed = Self::new(group_count);
+        let mut between: HashMap<(usize, usize), f64> = HashMap::new();
+        for node in 0..self.len() {
+            let group = groups[node];
+            if self.self_loops[node] > 0.0 {
+                collapsed.add_edge(group, group, self.self_loops[node]);
+            }
+            for &(neighbour, weight) in &self.neighbours[node] {
+                // Each edge appears in both nodes' lists; takes it once.
+                if node < neighbour {
+                    let other = groups[neighbour];
+                    *between
+                        .entry((group.min(other), group.max(other)))
+                        .or_default() += weight;
+                }
+            }
+        }
+
+        let mut edges: Vec<_> = between.into_iter().collect();
+        edges.sort_by_key(|&(pair, _)| pair);
+        for ((a, b), weight) in edges {
+            collapsed.add_edge(a, b, weight);
+        }
+        collapsed
+    }
+}
+
+impl Communities {
+    /// Wraps a division found by another method, such as SCCs, so that it can be
+    /// compared by modularity. The communities are renumbered from 0.
+    pub fn new(graph: &WeightedGraph, mut assignment: Vec<usize>) -> Self {
+        renumber(&mut assignment);
+        Self {
+            modularity: modularity(graph, &assignment),
+            assignment,
+            levels: 0,
+        }
+    }
+
+    /// Returns the number of communities.
+    pub fn count(&self) -> usize {
+        self.assignment.iter().max().map_or(0, |&max| max + 1)
+    }
+
+    /// Returns the nodes of each community, largest community first.
+    pub fn members(&self) -> Vec<Vec<usize>> {
+        let mut members = vec![Vec::new(); self.count()];
+        for (node, &community) in self.assignment.iter().enumerate() {
+            members[community].push(node);
+        }
+        members.sort_by_key(|community| std::cmp::Reverse(community.len()));
+        members
+    }
+}
+
+/// Computes the modularity of a division of the graph into communities.
+///
+/// # Example
+///
+/// ```
+/// use community_detection_kosaraju_algo::modularity::{modularity, WeightedGraph};
+///
+/// // Two pairs joined by nothing: each pair is a perfect community.
+/// let mut graph = WeightedGraph::new(4);
+/// graph.add_edge(0, 1, 1.0);
+/// graph.add_edge(2, 3, 1.0);
+/// assert_eq!(modularity(&graph, &[0, 0, 1, 1]), 0.5);
+/// ```
+pub fn modularity(graph: &WeightedGraph, assignment: &[usize]) -> f64 {
+    let two_m = 2.0 * graph.total_weight;
+    if two_m == 0.0 {
+        return 0.0;
+    }
+
+    let count = assignment.iter().max().map_or(0, |&max| max + 1);
+    let mut inside = vec![0.0; count];
+    let mut total = vec![0.0; count];
+    for node in 0..graph.len() {
+        let community = assignment[node];
+        total[community] += graph.degrees[node];
+        inside[community] += 2.0 * graph.self_loops[node];
+        for &(neighbour, weight) in &graph.neighbours[node] {
+            if assignment[neighbour] == community {
+                inside[community] += weight;
+            }
+        }
+    }
+
+    inside
+        .iter()
+        .zip(&total)
+        .map(|(inside, total)| inside / two_m - (total / two_m).powi(2))
+        .sum()
+}
+
+/// Detects communities with the Louvain algorithm.
+pub fn louvain(graph: &WeightedGraph) -> Communities {
+    detect(graph, false)
+}
+
+/// Detects communities with the Leiden algorithm, which refines each community
+/// before collapsing it so that every community is connected.
+pub fn leiden(graph: &WeightedGraph) -> Communities {
+    detect(graph, true)
+}
+
+fn detect(graph: &WeightedGraph, refine: bool) -> Communities {
+    // The node of the current level that each original node has been collapsed into.
+    let mut node_of: Vec<usize> = (0..graph.len()).collect();
+    let mut level_graph = graph.clone();
+    let mut partition: Vec<usize> = (0..graph.len()).collect();
+    let mut levels = 0;
+
+    while levels < MAX_LEVELS {
+        let moved = move_nodes(&level_graph, &mut partition);
+        let community_count = renumber(&mut partition);
+
+        // Leiden collapses the refined sub-communities; Louvain the communities.
+        let mut groups = partition.clone();
+        let mut group_count = community_count;
+        if refine {
+            groups = refine_partition(&level_graph, &partition, community_count);
+            group_count = renumber(&mut groups);
+        }
+
+        if !moved && group_count == level_graph.len() {
+            break;
+        }
+        levels += 1;
+
+        // Each collapsed group starts in the community that contains it.
+        let mut next_partition = vec![0; group_count];
+        for (node, &group) in groups.iter().enumerate() {
+            next_partition[group] = partition[node];
+        }
+        for node in node_of.iter_mut() {
+            *node = groups[*node];
+        }
+        level_graph = level_graph.aggregate(&groups, group_count);
+        partition = next_partition;
+    }
+
+    let mut assignment: Vec<usize> = node_of.iter().map(|&node| partition[node]).collect();
+    renumber(&mut assignment);
+    Communities {
+        modularity: modularity(graph, &assignment),
+        assignment,
+        levels,
+    }
+}
+
+// Moves single nodes to the neighbouring community with the largest modularity
+// gain until no move helps. Returns whether any node moved.
+fn move_nodes(graph: &WeightedGraph, partition: &mut [usize]) -> bool {
+    let two_m = 2.0 * graph.total_weight;
+    if two_m == 0.0 {
+        return false;
+    }
+
+    let mut total = vec![0.0; graph.len()];
+    for (node, &community) in partition.iter().enumerate() {
+        total[community] += graph.degrees[node];
+    }
+
+    let mut moved = false;
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for node in 0..graph.len() {
+            let current = partition[node];
+            let degree = graph.degrees[node];
+            let links = community_links(graph, node, |neighbour| Some(partition[neighbour]));
+
+            // The gain of joining a community, relative to being on its own. Ties
+            // keep the node where it is, then prefer the lowest community number.
+            total[current] -= degree;
+            let gain = |community: usize, weight: f64| weight - total[community] * degree / two_m;
+            let stay = links
+                .iter()
+                .find(|&&(community, _)| community == current)
+                .map_or(0.0, |&(_, weight)| weight);
+            let mut best = (current, gain(current, stay));
+            for &(community, weight) in &links {
+                let candidate = gain(community, weight);
+                if candidate > best.1 + MIN_GAIN {
+                    best = (community, candidate);
+                }
+            }
+            total[best.0] += degree;
+
+            if best.0 != current {
+                partition[node] = best.0;
+                improved = true;
+                moved = true;
+            }
+        }
+    }
+    moved
+}
+
+// Splits each community into well-connected sub-communities, starting from single
+// nodes and merging each still-single node into the best sub-community it is
+// linked to within its community.
+fn refine_partition(
+    graph: &WeightedGraph,
+    partition: &[usize],
+    community_count: usize,
+) -> Vec<usize> {
+    let two_m = 2.0 * graph.total_weight;
+    let mut refined: Vec<usize> = (0..graph.len()).collect();
+    if two_m == 0.0 {
+        return refined;
+    }
+
+    // The total degree of each community, and each node's links into its own community.
+    let mut community_total = vec![0.0; community_count];
+    let mut inside_links = vec![0.0; graph.len()];
+    for node in 0..graph.len() {
+        community_total[partition[node]] += graph.degrees[node];
+        inside_links[node] = graph.neighbours[node]
+            .iter()
+            .filter(|&&(neighbour, _)| partition[neighbour] == partition[node])
+            .map(|&(_, weight)| weight)
+            .sum();
+    }
+
+    // For each sub-community: its total degree, its links to the rest of its
+    // community, and whether it is still a single node.
+    let mut total = graph.degrees.clone();
+    let mut outside_links = inside_links.clone();
+    let mut single = vec![true; graph.len()];
+    let well_connected = |links: f64, total: f64, community: usize| {
+        links >= total * (community_total[community] - total) / two_m
+    };
+
+    for node in 0..graph.len() {
+        let community = partition[node];
+        let degree = graph.degrees[node];
+        if !single[node] || !well_connected(inside_links[node], degree, community) {
+            continue;
+        }
+
+        let links = community_links(graph, node, |neighbour| {
+            (partition[neighbour] == community && neighbour != node).then_some(refined[neighbour])
+        });
+        let mut best: Option<(usize, f64, f64)> = None;
+        for &(sub, weight) in &links {
+            if !well_connected(outside_links[sub], total[sub], community) {
+                continue;
+            }
+            let gain = weight - total[sub] * degree / two_m;
+            if gain >= 0.0 && best.is_none_or(|(_, best_gain, _)| gain > best_gain + MIN_GAIN) {
+                best = Some((sub, gain, weight));
+            }
+        }
+
+        if let Some((sub, _, weight)) = best {
+            refined[node] = sub;
+            total[sub] += degree;
+            outside_links[sub] += inside_links[node] - 2.0 * weight;
+            single[sub] = false;
+            single[node] = false;
+        }
+    }
+    refined
+}
+
+// Sums the weight of a node's edges to each group, in group order. `group_of`
+// gives a neighbour's group, or `None` to ignore it.
+fn community_links<F>(graph: &WeightedGraph, node: usize, group_of: F) -> Vec<(usize, f64)>
+where
+    F: Fn(usize) -> Option<usize>,
+{
+    let mut links: Vec<(usize, f64)> = graph.neighbours[node]
+        .iter()
+        .filter_map(|&(neighbour, weight)| group_of(neighbour).map(|group| (group, weight)))
+        .collect();
+    links.sort_by_key(|&(group, _)| group);
+    links.dedup_by(|(group, weight), (kept_group, kept_weight)| {
+        let same = group == kept_group;
+        if same {
+            *kept_weight += *weight;
+        }
+        same
+    });
+    links
+}
+
+// Numbers communities from 0 in order of first appearance, returning their count.
+pub(crate) fn renumber(assignment: &mut [usize]) -> usize {
+    let mut numbers = HashMap::new();
+    for community in assignment.iter_mut() {
+        let next = numbers.len();
+        *community = *numbers.entry(*community).or_insert(next);
+    }
+    numbers.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two triangles joined by a single edge between nodes 2 and 3.
+    fn two_triangles() -> WeightedGraph {
+        let mut graph = WeightedGraph::new(6);
+        for (a, b) in [(0, 1), (1, 2), (0, 2), (3, 4), (4, 5), (3, 5), (2, 3)] {
+            graph.add_edge(a, b, 1.0);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_modularity_of_two_triangles() {
+        let graph = two_triangles();
+        let q = modularity(&graph, &[0, 0, 0, 1, 1, 1]);
+        assert!((q - 5.0 / 14.0).abs() < 1e-12);
+        assert_eq!(modularity(&graph, &[0; 6]), 0.0);
+    }
+
+    #[test]
+    fn test_louvain_and_leiden_find_the_triangles() {
+        let graph = two_triangles();
+        for communities in [louvain(&graph), leiden(&graph)] {
+            assert_eq!(communities.assignment, vec![0, 0, 0, 1, 1, 1]);
+            assert!((communities.modularity - 5.0 / 14.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_collapsing_keeps_modularity() {
+        // Four triangles in a ring, each joined to the next by one edge.
+        let mut graph = WeightedGraph::new(12);
+        for t in 0..4 {
+            let base = 3 * t;
+            graph.add_edge(base, base + 1, 1.0);
+            graph.add_edge(base + 1, base + 2, 1.0);
+            graph.add_edge(base, base + 2, 1.0);
+            graph.add_edge(base + 2, (base + 3) % 12, 1.0);
+        }
+
+        let groups: Vec<usize> = (0..12).map(|node| node / 3).collect();
+        let collapsed = graph.aggregate(&groups, 4);
+        assert_eq!(collapsed.total_weight(), graph.total_weight());
+        assert!(
+            (modularity(&collapsed, &[0, 1, 2, 3]) - modularity(&graph, &groups)).abs() < 1e-12
+        );
+
+        let communities = leiden(&graph);
+        assert_eq!(communities.count(), 4);
+        assert!(communities
+            .members()
+            .iter()
+            .all(|members| members.len() == 3));
+    }
+}
@@ -0,0 +1,285 @@
+/* Comparing the communities of two snapshots of a dataset.
+
+Communities are matched across the snapshots by the Jaccard overlap of their
+members, |A ∩ B| / |A ∪ B|: a community before and one after match when their
+overlap reaches the threshold. From the matches, each community's fate is one of:
+
+- born: an after community that matches nothing before;
+- died: a before community that matches nothing after;
+- merged: an after community matched by two or more before communities;
+- split: a before community matched by two or more after communities;
+- grew, shrank or continued: a before and an after community that only match
+  each other, with more, fewer or the same number of members.
+
+The report lists both snapshots' communities, numbered from 0 largest first, and
+the events between them, and can be written as JSON.
+*/
+
+use serde::Serialize;
+use std::collections::HashSet;
+use std::error::Error;
+use std::io::Write;
+
+/// The default Jaccard overlap at which two communities match.
+pub const DEFAULT_MATCH_THRESHOLD: f64 = 0.3;
+
+/// A community in one snapshot.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Community {
+    /// The community's number within its snapshot.
+    pub id: usize,
+    pub size: usize,
+    /// The members' names, sorted.
+    pub members: Vec<String>,
+}
+
+/// A change between the two snapshots. Communities are referred to by their ids.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum CommunityEvent {
+    Born {
+        after: usize,
+    },
+    Died {
+        before: usize,
+    },
+    Merged {
+        before: Vec<usize>,
+        after: usize,
+    },
+    Split {
+        before: usize,
+        after: Vec<usize>,
+    },
+    Grew {
+        before: usize,
+        after: usize,
+        jaccard: f64,
+        joined: Vec<String>,
+        left: Vec<String>,
+    },
+    Shrank {
+        before: usize,
+        after: usize,
+        jaccard: f64,
+        joined: Vec<String>,
+        left: Vec<String>,
+    },
+    Continued {
+        before: usize,
+        after: usize,
+        jaccard: f64,
+        joined: Vec<String>,
+        left: Vec<String>,
+    },
+}
+
+/// The communities of two snapshots and the events between them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnapshotDiff {
+    /// The Jaccard overlap at which two communities were matched.
+    pub match_threshold: f64,
+    pub before: Vec<Community>,
+    pub after: Vec<Community>,
+    pub events: Vec<CommunityEvent>,
+}
+
+impl SnapshotDiff {
+    /// Compares two snapshots, each given as the member names of its communities.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use community_detection_kosaraju_algo::snapshot::{CommunityEvent, SnapshotDiff};
+    ///
+    /// let before = vec![vec!["a", "b"], vec!["c", "d"]];
+    /// let after = vec![vec!["a", "b", "c", "d"]];
+    /// let diff = SnapshotDiff::new(&before, &after, 0.3);
+    /// assert_eq!(
+    ///     diff.events,
+    ///     vec![CommunityEvent::Merged { before: vec![0, 1], after: 0 }]
+    /// );
+    /// ```
+    pub fn new<S: AsRef<str>>(before: &[Vec<S>], after: &[Vec<S>], match_threshold: f64) -> Self {
+        let before = snapshot(before);
+        let after = snapshot(after);
+
+        // The after communities each before community matches, and vice versa.
+        let mut matches_after = vec![Vec::new(); before.len()];
+        let mut matches_before = vec![Vec::new(); after.len()];
+        for (i, old) in before.iter().enumerate() {
+            for (j, new) in after.iter().enumerate() {
+                if jaccard(&old.members, &new.members) >= match_threshold {
+                    matches_after[i].push(j);
+                    matches_before[j].push(i);
+                }
+            }
+        }
+
+        let mut events = Vec::new();
+        for (i, matches) in matches_after.iter().enumerate() {
+            match matches.as_slice() {
+                [] => events.push(CommunityEvent::Died { before: i }),
+                [j] if matches_before[*j].len() == 1 => {
+                    events.push(continuation(&before[i], &after[*j]))
+                }
+                [_] => {}
+                _ => events.push(CommunityEvent::Split {
+                    before: i,
+                    after: matches.clone(),
+                }),
+            }
+        }
+        for (j, matches) in matches_before.iter().enumerate() {
+            match matches.len() {
+                0 => events.push(CommunityEvent::Born { after: j }),
+                1 => {}
+                _ => events.push(CommunityEvent::Merged {
+                    before: matches.clone(),
+                    after: j,
+                }),
+            }
+        }
+
+        Self {
+            match_threshold,
+            before,
+            after,
+            events,
+        }
+    }
+
+    /// Writes the report as pretty-printed JSON.
+    pub fn write_json<W: Write>(&self, writer: W) -> Result<(), Box<dyn Error>> {
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+}
+
+/// Returns the Jaccard overlap of two sets of names; 0 if both are empty.
+pub fn jaccard<S: AsRef<str>>(a: &[S], b: &[S]) -> f64 {
+    let a: HashSet<&str> = a.iter().map(AsRef::as_ref).collect();
+    let b: HashSet<&str> = b.iter().map(AsRef::as_ref).collect();
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+// Numbers the communities largest first, breaking ties by their first member.
+fn snapshot<S: AsRef<str>>(communities: &[Vec<S>]) -> Vec<Community> {
+    let mut members: Vec<Vec<String>> = communities
+        .iter()
+        .map(|community| {
+            let mut names: Vec<String> = community.iter().map(|s| s.as_ref().to_string()).collect();
+            names.sort();
+            names.dedup();
+            names
+        })
+        .collect();
+    members.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+
+    members
+        .into_iter()
+        .enumerate()
+        .map(|(id, members)| Community {
+            id,
+            size: members.len(),
+            members,
+        })
+        .collect()
+}
+
+// Describes a before community that continues as a single after community.
+fn continuation(before: &Community, after: &Community) -> CommunityEvent {
+    let jaccard = jaccard(&before.members, &after.members);
+    let joined = difference(&after.members, &before.members);
+    let left = difference(&before.members, &after.members);
+    let (before, after) = (before.id, after.id);
+    // The community grows by the members who joined and shrinks by those who left.
+    match joined.len().cmp(&left.len()) {
+        std::cmp::Ordering::Greater => CommunityEvent::Grew {
+            before,
+            after,
+            jaccard,
+            joined,
+            left,
+        },
+        std::cmp::Ordering::Less => CommunityEvent::Shrank {
+            before,
+            after,
+            jaccard,
+            joined,
+            left,
+        },
+        std::cmp::Ordering::Equal => CommunityEvent::Continued {
+            before,
+            after,
+            jaccard,
+            joined,
+            left,
+        },
+    }
+}
+
+// The sorted names in `a` but not in `b`.
+fn difference(a: &[String], b: &[String]) -> Vec<String> {
+    let b: HashSet<&String> = b.iter().collect();
+    a.iter().filter(|name| !b.contains(name)).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events() {
+        let before = vec![
+            vec!["a", "b", "c", "d"],
+            vec!["e", "f", "g", "h", "i", "j"],
+            vec!["k", "l"],
+        ];
+        let after = vec![
+            vec!["a", "b", "c", "d", "x"],
+            vec!["e", "f", "g"],
+            vec!["h", "i", "j"],
+            vec!["y", "z"],
+        ];
+        let diff = SnapshotDiff::new(&before, &after, DEFAULT_MATCH_THRESHOLD);
+
+        // Sorted largest first: before [e..j], [a..d], [k, l]; after [a..d, x], then
+        // [e, f, g], [h, i, j], [y, z].
+        assert_eq!(diff.before[0].members[0], "e");
+        assert_eq!(
+            diff.events,
+            vec![
+                CommunityEvent::Split {
+                    before: 0,
+                    after: vec![1, 2]
+                },
+                CommunityEvent::Grew {
+                    before: 1,
+                    after: 0,
+                    jaccard: 0.8,
+                    joined: vec!["x".to_string()],
+                    left: vec![],
+                },
+                CommunityEvent::Died { before: 2 },
+                CommunityEvent::Born { after: 3 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_report() {
+        let diff = SnapshotDiff::new(&[vec!["a"]], &[vec!["b"]], 0.5);
+        let mut output = Vec::new();
+        diff.write_json(&mut output).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+        assert_eq!(json["events"][0]["event"], "died");
+        assert_eq!(json["events"][1]["event"], "born");
+        assert_eq!(json["after"][0]["members"][0], "b");
+    }
+}
@@ -0,0 +1,321 @@
+/* Centrality measures for fighter networks.
+
+Every measure ignores edge direction and weight, and counts parallel edges (a
+rematch) once, so a fighter's opponents are simply their neighbours:
+
+- degree: the share of the other fighters a fighter has faced;
+- closeness: the harmonic mean of the inverse distances to the other fighters,
+  where fighters who cannot be reached add 0, so it stays defined when the
+  network is disconnected;
+- betweenness: the share of shortest paths between pairs of other fighters that
+  pass through a fighter, counted with Brandes' algorithm;
+- eigenvector: a fighter's score is proportional to the sum of their opponents'
+  scores, found by power iteration.
+
+Degree, closeness and betweenness are normalised to lie between 0 and 1; the
+eigenvector scores have unit length.
+*/
+
+use petgraph::graph::{Graph, NodeIndex};
+use petgraph::EdgeType;
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+/// The maximum number of power iterations for eigenvector centrality.
+pub const EIGENVECTOR_MAX_ITERATIONS: usize = 1000;
+
+/// The convergence tolerance on the L1 change in eigenvector scores between iterations.
+pub const EIGENVECTOR_TOLERANCE: f64 = 1e-10;
+
+/// A centrality measure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Centrality {
+    Degree,
+    Closeness,
+    Betweenness,
+    Eigenvector,
+}
+
+impl Centrality {
+    /// Every measure, in the order they are reported.
+    pub const ALL: [Centrality; 4] = [
+        Centrality::Degree,
+        Centrality::Closeness,
+        Centrality::Betweenness,
+        Centrality::Eigenvector,
+    ];
+
+    /// Computes the measure for every node, in node index order.
+    pub fn compute<N, E, Ty: EdgeType>(self, graph: &Graph<N, E, Ty>) -> Vec<f64> {
+        match self {
+            Centrality::Degree => degree(graph),
+            Centrality::Closeness => closeness(graph),
+            Centrality::Betweenness => betweenness(graph),
+            Centrality::Eigenvector => eigenvector(graph),
+        }
+    }
+}
+
+impl fmt::Display for Centrality {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Centrality::Degree => "degree",
+            Centrality::Closeness => "closeness",
+            Centrality::Betweenness => "betweenness",
+            Centrality::Eigenvector => "eigenvector",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Centrality {
+    type Err = String;
+
+    /// Parses a measure by the name it is displayed with.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Centrality::ALL
+            .into_iter()
+            .find(|measure| measure.to_string() == text)
+            .ok_or_else(|| {
+                format!(
+                    "unknown centrality `{}`; expected degree, closeness, betweenness or eigenvector",
+                    text
+                )
+            })
+    }
+}
+
+/// Returns the share of the other fighters each fighter has faced.
+pub fn degree<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<f64> {
+    let others = normaliser(graph.node_count().saturating_sub(1));
+    neighbours(graph)
+        .iter()
+        .map(|adjacent| adjacent.len() as f64 / others)
+        .collect()
+}
+
+/// Returns the harmonic closeness of each fighter: the sum of 1 / distance to
+/// every other fighter, divided by the number of other fighters.
+pub fn closeness<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<f64> {
+    let adjacency = neighbours(graph);
+    let others = normaliser(graph.node_count().saturating_sub(1));
+    (0..adjacency.len())
+        .map(|source| {
+            let inverse: f64 = distances(&adjacency, source)
+                .into_iter()
+                .flatten()
+                .filter(|&distance| distance > 0)
+                .map(|distance| 1.0 / distance as f64)
+                .sum();
+            inverse / others
+        })
+        .collect()
+}
+
+/// Returns the betweenness of each fighter with Brandes' algorithm, divided by
+/// the number of pairs of other fighters.
+pub fn betweenness<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<f64> {
+    let adjacency = neighbours(graph);
+    let n = adjacency.len();
+    let mut centrality = vec![0.0; n];
+
+    for source in 0..n {
+        // Breadth-first search, counting the shortest paths to each node and
+        // recording the nodes that precede it on them.
+        let mut order = Vec::with_capacity(n);
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut paths = vec![0.0; n];
+        let mut distance: Vec<Option<usize>> = vec![None; n];
+        paths[source] = 1.0;
+        distance[source] = Some(0);
+        let mut queue = VecDeque::from([source]);
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            let next = distance[node].unwrap() + 1;
+            for &neighbour in &adjacency[node] {
+                if distance[neighbour].is_none() {
+                    distance[neighbour] = Some(next);
+                    queue.push_back(neighbour);
+                }
+                if distance[neighbour] == Some(next) {
+                    paths[neighbour] += paths[node];
+                    predecessors[neighbour].push(node);
+                }
+            }
+        }
+
+        // Accumulate the dependencies from the farthest nodes back.
+        let mut dependency = vec![0.0; n];
+        for &node in order.iter().rev() {
+            for &predecessor in &predecessors[node] {
+                dependency[predecessor] +=
+                    paths[predecessor] / paths[node] * (1.0 + dependency[node]);
+            }
+            if node != source {
+                centrality[node] += dependency[node];
+            }
+        }
+    }
+
+    // Every pair was counted from both ends.
+    let pairs = normaliser(n.saturating_sub(1) * n.saturating_sub(2));
+    centrality.iter().map(|total| total / pairs).collect()
+}
+
+/// Returns the eigenvector centrality of each fighter, scaled to unit length.
+///
+/// The power iteration multiplies by the adjacency matrix plus the identity,
+/// which has the same leading eigenvector but also converges on bipartite
+/// networks, where the adjacency matrix alone would oscillate.
+pub fn eigenvector<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<f64> {
+    let adjacency = neighbours(graph);
+    let n = adjacency.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut scores = vec![1.0 / (n as f64).sqrt(); n];
+
+    for _ in 0..EIGENVECTOR_MAX_ITERATIONS {
+        let mut next: Vec<f64> = adjacency
+            .iter()
+            .enumerate()
+            .map(|(node, adjacent)| {
+                scores[node] + adjacent.iter().map(|&other| scores[other]).sum::<f64>()
+            })
+            .collect();
+        let length = next.iter().map(|score| score * score).sum::<f64>().sqrt();
+        for score in &mut next {
+            *score /= length;
+        }
+
+        let change: f64 = next
+            .iter()
+            .zip(&scores)
+            .map(|(new, old)| (new - old).abs())
+            .sum();
+        scores = next;
+        if change < EIGENVECTOR_TOLERANCE {
+            break;
+        }
+    }
+    scores
+}
+
+/// Returns the node indexes and scores sorted from the highest score, breaking
+/// ties by node index.
+pub fn ranking(scores: &[f64]) -> Vec<(usize, f64)> {
+    let mut ranked: Vec<(usize, f64)> = scores.iter().copied().enumerate().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    ranked
+}
+
+// The distinct neighbours of each node, ignoring direction and self-loops, sorted
+// so that the measures do not depend on edge order.
+pub(crate) fn neighbours<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<Vec<usize>> {
+    graph
+        .node_indices()
+        .map(|node| {
+            let adjacent: HashSet<NodeIndex> = graph
+                .neighbors_undirected(node)
+                .filter(|&other| other != node)
+                .collect();
+            let mut adjacent: Vec<usize> = adjacent.into_iter().map(|n| n.index()).collect();
+            adjacent.sort_unstable();
+            adjacent
+        })
+        .collect()
+}
+
+// The hop count from the source to every node, or `None` if it cannot be reached.
+fn distances(adjacency: &[Vec<usize>], source: usize) -> Vec<Option<usize>> {
+    let mut distance = vec![None; adjacency.len()];
+    distance[source] = Some(0);
+    let mut queue = VecDeque::from([source]);
+    while let Some(node) = queue.pop_front() {
+        let next = distance[node].map(|d| d + 1);
+        for &neighbour in &adjacency[node] {
+            if distance[neighbour].is_none() {
+                distance[neighbour] = next;
+                queue.push_back(neighbour);
+            }
+        }
+    }
+    distance
+}
+
+// A divisor for normalising, which is 1 when there is nothing to divide by.
+fn normaliser(count: usize) -> f64 {
+    count.max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::graph::UnGraph;
+
+    // A path a - b - c - d, and a separate fighter e.
+    fn path() -> UnGraph<&'static str, f32> {
+        let mut graph = UnGraph::new_undirected();
+        let nodes: Vec<NodeIndex> = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|&name| graph.add_node(name))
+            .collect();
+        for pair in nodes[..4].windows(2) {
+            graph.add_edge(pair[0], pair[1], 1.0);
+        }
+        // A rematch counts once.
+        graph.add_edge(nodes[0], nodes[1], 1.0);
+        graph
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_degree_and_closeness() {
+        let graph = path();
+        assert_close(&degree(&graph), &[0.25, 0.5, 0.5, 0.25, 0.0]);
+        // a reaches b, c and d at distances 1, 2 and 3; e reaches no one.
+        let a = (1.0 + 1.0 / 2.0 + 1.0 / 3.0) / 4.0;
+        let b = (1.0 + 1.0 + 1.0 / 2.0) / 4.0;
+        assert_close(&closeness(&graph), &[a, b, b, a, 0.0]);
+    }
+
+    #[test]
+    fn test_betweenness() {
+        // b lies on the paths a-c and a-d, out of the 6 pairs of the 4 others.
+        assert_close(
+            &betweenness(&path()),
+            &[0.0, 2.0 / 6.0, 2.0 / 6.0, 0.0, 0.0],
+        );
+
+        // In a 4-cycle, each node lies on half of the two shortest paths between
+        // its neighbours.
+        let mut cycle = UnGraph::<(), ()>::new_undirected();
+        let nodes: Vec<NodeIndex> = (0..4).map(|_| cycle.add_node(())).collect();
+        for i in 0..4 {
+            cycle.add_edge(nodes[i], nodes[(i + 1) % 4], ());
+        }
+        assert_close(&betweenness(&cycle), &[1.0 / 6.0; 4]);
+    }
+
+    #[test]
+    fn test_eigenvector_of_a_star() {
+        // The hub of a star with k leaves scores sqrt(k) times each leaf.
+        let mut star = UnGraph::<(), ()>::new_undirected();
+        let hub = star.add_node(());
+        for _ in 0..4 {
+            let leaf = star.add_node(());
+            star.add_edge(hub, leaf, ());
+        }
+        let scores = eigenvector(&star);
+        assert!((scores[0] / scores[1] - 2.0).abs() < 1e-6);
+        assert!((scores.iter().map(|s| s * s).sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(ranking(&scores)[0].0, 0);
+    }
+}
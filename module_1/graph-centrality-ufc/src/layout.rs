@@ -0,0 +1,421 @@
+/* Force-directed layout with the Fruchterman–Reingold algorithm, and SVG rendering.
+
+Nodes repel each other like charged particles, with a force of k² / d, and every
+edge pulls its ends together like a spring, with a force of d² / k, where d is
+the distance between the nodes and k the ideal edge length, sqrt(area / n). The
+nodes start evenly spaced on a circle, so the layout is the same on every run,
+and each iteration moves every node along its net force, by at most the current
+temperature. The temperature cools linearly to 0, so the layout settles.
+
+Edge direction is ignored while laying out. The layout fills the unit square,
+and `write_svg` scales it to the picture, drawing edges (with arrows for
+directed graphs), then nodes sized and coloured by a score, then their labels.
+
+Both work on any petgraph graph (`Graph`, `StableGraph`, `GraphMap`, ...), with
+positions and scores indexed by `NodeIndexable::to_index`, which is
+`NodeIndex::index()` for `Graph`.
+*/
+
+use graph_export::escape_xml;
+use petgraph::visit::{
+    EdgeRef, GraphProp, IntoEdgeReferences, IntoNeighbors, IntoNodeIdentifiers, IntoNodeReferences,
+    NodeIndexable, NodeRef,
+};
+use std::f64::consts::PI;
+use std::fmt::Display;
+use std::io::{self, Write};
+
+/// The default number of layout iterations.
+pub const DEFAULT_ITERATIONS: usize = 500;
+
+/// The width and height of the rendered SVG, in pixels.
+pub const SVG_WIDTH: f64 = 800.0;
+pub const SVG_HEIGHT: f64 = 600.0;
+
+// The space around the layout in the SVG, leaving room for labels.
+const MARGIN: f64 = 80.0;
+
+// The node radius range in the SVG; scores are scaled into it.
+const MIN_RADIUS: f64 = 6.0;
+const MAX_RADIUS: f64 = 20.0;
+
+/// A Fruchterman–Reingold layout, with the number of iterations to run.
+pub struct FruchtermanReingold {
+    iterations: usize,
+}
+
+impl Default for FruchtermanReingold {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FruchtermanReingold {
+    /// Creates a layout with the default number of iterations.
+    pub fn new() -> Self {
+        Self {
+            iterations: DEFAULT_ITERATIONS,
+        }
+    }
+
+    /// Sets the number of iterations.
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Returns the position of every node within the unit square, indexed by
+    /// `NodeIndexable::to_index`. Indexes without a node, which graphs such as
+    /// `StableGraph` leave behind after removals, are placed at the centre.
+    pub fn layout<G>(&self, graph: G) -> Vec<(f64, f64)>
+    where
+        G: IntoNeighbors + IntoNodeIdentifiers + NodeIndexable,
+    {
+        let (indexes, adjacency) = neighbours(graph);
+        let n = indexes.len();
+        let mut layout = vec![(0.5, 0.5); graph.node_bound()];
+        if n == 0 {
+            return layout;
+        }
+
+        let mut positions: Vec<(f64, f64)> = (0..n)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f64 / n as f64;
+                (0.5 + 0.4 * angle.cos(), 0.5 + 0.4 * angle.sin())
+            })
+            .collect();
+        let k = (1.0 / n as f64).sqrt();
+
+        for iteration in 0..self.iterations {
+            let temperature = 0.1 * (1.0 - iteration as f64 / self.iterations as f64);
+            let mut displacement = vec![(0.0, 0.0); n];
+
+            for a in 0..n {
+                // Repulsion between every pair of nodes
+                for b in a + 1..n {
+                    let (dx, dy, distance) = offset(positions[a], positions[b]);
+                    let force = k * k / distance;
+                    let (fx, fy) = (dx / distance * force, dy / distance * force);
+                    displacement[a].0 += fx;
+                    displacement[a].1 += fy;
+                    displacement[b].0 -= fx;
+                    displacement[b].1 -= fy;
+                }
+                // Attraction along each edge, counted once from its lower end
+                for &b in adjacency[a].iter().filter(|&&b| b > a) {
+                    let (dx, dy, distance) = offset(positions[a], positions[b]);
+                    let force = distance * distance / k;
+                    let (fx, fy) = (dx / distance * force, dy / distance * force);
+                    displacement[a].0 -= fx;
+                    displacement[a].1 -= fy;
+                    displacement[b].0 += fx;
+                    displacement[b].1 += fy;
+                }
+            }
+
+            for (position, (dx, dy)) in positions.iter_mut().zip(displacement) {
+                let length = (dx * dx + dy * dy).sqrt();
+                if length > 0.0 {
+                    let step = length.min(temperature);
+                    position.0 = (position.0 + dx / length * step).clamp(0.0, 1.0);
+                    position.1 = (position.1 + dy / length * step).clamp(0.0, 1.0);
+                }
+            }
+        }
+
+        fit(&mut positions);
+        for (index, position) in indexes.into_iter().zip(positions) {
+            layout[index] = position;
+        }
+        layout
+    }
+}
+
+// The index of every node, and the distinct neighbours of each, by their position
+// in that list, ignoring direction and self-loops and sorted so that the layout does
+// not depend on edge order.
+fn neighbours<G>(graph: G) -> (Vec<usize>, Vec<Vec<usize>>)
+where
+    G: IntoNeighbors + IntoNodeIdentifiers + NodeIndexable,
+{
+    let indexes: Vec<usize> = graph
+        .node_identifiers()
+        .map(|node| graph.to_index(node))
+        .collect();
+    let mut position = vec![0; graph.node_bound()];
+    for (i, &index) in indexes.iter().enumerate() {
+        position[index] = i;
+    }
+
+    let mut adjacency = vec![Vec::new(); indexes.len()];
+    for node in graph.node_identifiers() {
+        let a = position[graph.to_index(node)];
+        for other in graph.neighbors(node) {
+            let b = position[graph.to_index(other)];
+            if a != b {
+                adjacency[a].push(b);
+                adjacency[b].push(a);
+            }
+        }
+    }
+    for adjacent in &mut adjacency {
+        adjacent.sort_unstable();
+        adjacent.dedup();
+    }
+    (indexes, adjacency)
+}
+
+// The vector from b to a and its length, nudged away from 0 so that nodes on
+// top of each other still push apart.
+fn offset(a: (f64, f64), b: (f64, f64)) -> (f64, f64, f64) {
+    let (dx, dy) = (a.0 - b.0, a.1 - b.1);
+    let distance = (dx * dx + dy * dy).sqrt();
+    if distance < 1e-9 {
+        (1e-9, 0.0, 1e-9)
+    } else {
+        (dx, dy, distance)
+    }
+}
+
+// Stretches the positions to fill the unit square in each direction that has any extent.
+fn fit(positions: &mut [(f64, f64)]) {
+    let fold = |pick: fn(&(f64, f64)) -> f64| {
+        let min = positions.iter().map(pick).fold(f64::INFINITY, f64::min);
+        let max = positions.iter().map(pick).fold(f64::NEG_INFINITY, f64::max);
+        (min, max)
+    };
+    let (x_min, x_max) = fold(|p| p.0);
+    let (y_min, y_max) = fold(|p| p.1);
+    let scale = |value: f64, min: f64, max: f64| {
+        if max > min {
+            (value - min) / (max - min)
+        } else {
+            0.5
+        }
+    };
+    for position in positions.iter_mut() {
+        *position = (
+            scale(position.0, x_min, x_max),
+            scale(position.1, y_min, y_max),
+        );
+    }
+}
+
+/// Draws the graph at the given positions as SVG, labelling every node with its
+/// `Display` text and sizing and colouring it by its score: the higher the score,
+/// the larger and redder the node. Positions and scores are indexed by
+/// `NodeIndexable::to_index`, and fail the write unless there is one per index.
+pub fn write_svg<G, W>(
+    graph: G,
+    positions: &[(f64, f64)],
+    scores: &[f64],
+    mut writer: W,
+) -> io::Result<()>
+where
+    G: IntoNodeReferences + IntoEdgeReferences + NodeIndexable + GraphProp,
+    G::NodeWeight: Display,
+    W: Write,
+{
+    let bound = graph.node_bound();
+    if positions.len() != bound || scores.len() != bound {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "got {} positions and {} scores for a graph of {} node indexes",
+                positions.len(),
+                scores.len(),
+                bound
+            ),
+        ));
+    }
+
+    // Only indexes that hold a node count towards the score range.
+    let present = || {
+        graph
+            .node_identifiers()
+            .map(|node| scores[graph.to_index(node)])
+    };
+    let min = present().fold(f64::INFINITY, f64::min);
+    let max = present().fold(f64::NEG_INFINITY, f64::max);
+    let relative: Vec<f64> = scores
+        .iter()
+        .map(|&score| {
+            if max > min {
+                (score - min) / (max - min)
+            } else {
+                0.5
+            }
+        })
+        .collect();
+    let radius: Vec<f64> = relative
+        .iter()
+        .map(|r| MIN_RADIUS + (MAX_RADIUS - MIN_RADIUS) * r)
+        .collect();
+    let point: Vec<(f64, f64)> = positions
+        .iter()
+        .map(|&(x, y)| {
+            (
+                MARGIN + x * (SVG_WIDTH - 2.0 * MARGIN),
+                MARGIN + y * (SVG_HEIGHT - 2.0 * MARGIN),
+            )
+        })
+        .collect();
+
+    writeln!(
+        writer,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = SVG_WIDTH,
+        h = SVG_HEIGHT
+    )?;
+    writeln!(
+        writer,
+        r#"  <rect width="100%" height="100%" fill="white"/>"#
+    )?;
+    if graph.is_directed() {
+        writeln!(
+            writer,
+            r##"  <defs><marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="6" markerHeight="6" orient="auto"><path d="M 0 0 L 10 5 L 0 10 z" fill="#999999"/></marker></defs>"##
+        )?;
+    }
+
+    writeln!(writer, r##"  <g stroke="#999999" stroke-width="1.5">"##)?;
+    for edge in graph.edge_references() {
+        let (source, target) = (graph.to_index(edge.source()), graph.to_index(edge.target()));
+        if source == target {
+            continue;
+        }
+        let (x1, y1) = point[source];
+        let (mut x2, mut y2) = point[target];
+        let arrow = if graph.is_directed() {
+            // End the line at the target's edge, so the arrow head shows.
+            let (dx, dy) = (x2 - x1, y2 - y1);
+            let length = (dx * dx + dy * dy).sqrt().max(1e-9);
+            x2 -= dx / length * radius[target];
+            y2 -= dy / length * radius[target];
+            r#" marker-end="url(#arrow)""#
+        } else {
+            ""
+        };
+        writeln!(
+            writer,
+            r#"    <line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}"{}/>"#,
+            x1, y1, x2, y2, arrow
+        )?;
+    }
+    writeln!(writer, "  </g>")?;
+
+    writeln!(writer, r##"  <g stroke="#333333" stroke-width="1">"##)?;
+    for node in graph.node_identifiers() {
+        let node = graph.to_index(node);
+        let (x, y) = point[node];
+        writeln!(
+            writer,
+            r#"    <circle cx="{:.1}" cy="{:.1}" r="{:.1}" fill="{}"/>"#,
+            x,
+            y,
+            radius[node],
+            colour(relative[node])
+        )?;
+    }
+    writeln!(writer, "  </g>")?;
+
+    writeln!(
+        writer,
+        r#"  <g font-family="sans-serif" font-size="12" text-anchor="middle">"#
+    )?;
+    for node in graph.node_references() {
+        let index = graph.to_index(node.id());
+        let (x, y) = point[index];
+        writeln!(
+            writer,
+            r#"    <text x="{:.1}" y="{:.1}">{}</text>"#,
+            x,
+            y + radius[index] + 14.0,
+            escape_xml(&node.weight().to_string())
+        )?;
+    }
+    writeln!(writer, "  </g>")?;
+    writeln!(writer, "</svg>")
+}
+
+// Blends from pale blue for the lowest score to red for the highest.
+fn colour(relative: f64) -> String {
+    let blend = |low: f64, high: f64| (low + (high - low) * relative).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        blend(158.0, 214.0),
+        blend(202.0, 39.0),
+        blend(225.0, 40.0)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::graph::{DiGraph, UnGraph};
+    use petgraph::graphmap::UnGraphMap;
+    use petgraph::stable_graph::StableUnGraph;
+
+    fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+        ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn test_linked_nodes_end_up_closer() {
+        // Two triangles joined by the edge 2 - 3.
+        let graph =
+            UnGraph::<(), ()>::from_edges([(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)]);
+        let positions = FruchtermanReingold::new().layout(&graph);
+
+        assert_eq!(positions, FruchtermanReingold::new().layout(&graph));
+        assert!(positions
+            .iter()
+            .all(|&(x, y)| (0.0..=1.0).contains(&x) && (0.0..=1.0).contains(&y)));
+        assert!(distance(positions[0], positions[1]) < distance(positions[0], positions[4]));
+        assert!(distance(positions[3], positions[5]) < distance(positions[5], positions[1]));
+    }
+
+    #[test]
+    fn test_svg() {
+        let mut graph = DiGraph::<&str, ()>::new();
+        let a = graph.add_node("Conor <The Notorious> McGregor");
+        let b = graph.add_node("Jose Aldo");
+        graph.add_edge(a, b, ());
+        let positions = FruchtermanReingold::new().layout(&graph);
+
+        let mut output = Vec::new();
+        write_svg(&graph, &positions, &[1.0, 0.0], &mut output).unwrap();
+        let svg = String::from_utf8(output).unwrap();
+        assert!(svg.contains(r#"marker-end="url(#arrow)""#));
+        assert!(svg.contains(r##"r="20.0" fill="#d62728""##));
+        assert!(svg.contains(r##"r="6.0" fill="#9ecae1""##));
+        assert!(svg.contains("Conor &lt;The Notorious&gt; McGregor"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_any_petgraph_graph() {
+        let edges = [(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)];
+        let graph = UnGraph::<(), ()>::from_edges(edges);
+        let map = UnGraphMap::<u32, ()>::from_edges(edges);
+        let expected = FruchtermanReingold::new().layout(&graph);
+        assert_eq!(FruchtermanReingold::new().layout(&map), expected);
+
+        // Removing a node leaves its index unused in a StableGraph.
+        let mut stable = StableUnGraph::<&str, ()>::from_edges(edges);
+        for (i, node) in ["a", "b", "c", "d", "e", "f"].into_iter().enumerate() {
+            stable[petgraph::stable_graph::NodeIndex::new(i)] = node;
+        }
+        stable.remove_node(petgraph::stable_graph::NodeIndex::new(1));
+        let positions = FruchtermanReingold::new().layout(&stable);
+        assert_eq!(positions.len(), 6);
+        assert_eq!(positions[1], (0.5, 0.5));
+
+        let mut output = Vec::new();
+        write_svg(&stable, &positions, &[1.0; 6], &mut output).unwrap();
+        let svg = String::from_utf8(output).unwrap();
+        assert_eq!(svg.matches("<circle").count(), 5);
+        assert!(!svg.contains(">b</text>"));
+        assert!(write_svg(&stable, &positions, &[1.0; 5], Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,20 @@
+/* Network analysis of UFC fighters.
+
+Fighters are nodes and fights are edges. The `centrality` module measures how
+central each fighter is in the network: by how many opponents they have faced,
+how close they are to everyone else, how often they lie on the shortest paths
+between other fighters, and how well connected their opponents are.
+
+The `records` module loads real fight histories, with winners, dates, methods
+and weight classes, into a directed graph from each winner to the loser. The
+`link_prediction` module scores the matchups that have not happened yet, and the
+`ratings` module replays the results in date order to rate every fighter with
+Elo and Glicko-2. The `layout` module places the fighters with a force-directed
+layout and draws the network as SVG.
+*/
+
+pub mod centrality;
+pub mod layout;
+pub mod link_prediction;
+pub mod ratings;
+pub mod records;
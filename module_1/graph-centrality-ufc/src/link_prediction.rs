@@ -0,0 +1,174 @@
+/* Link prediction: scoring the fights that have not happened yet.
+
+For every pair of fighters who have never met, four classic scores estimate how
+"natural" the matchup is from the opponents they have faced, ignoring direction:
+
+- common neighbours: the number of opponents both have faced;
+- Jaccard: common opponents as a share of all their opponents;
+- Adamic–Adar: the sum over common opponents of 1 / ln(their number of
+  opponents), so a shared opponent counts for more when they have faced few;
+- preferential attachment: the product of their numbers of opponents, favouring
+  two busy fighters.
+
+Candidates are ranked by one of the scores, with ties going to the pair whose
+fighters come first in the graph.
+*/
+
+use crate::centrality::neighbours;
+use petgraph::graph::Graph;
+use petgraph::EdgeType;
+use std::fmt;
+use std::str::FromStr;
+
+/// A score that ranks candidate matchups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkMetric {
+    CommonNeighbours,
+    Jaccard,
+    AdamicAdar,
+    PreferentialAttachment,
+}
+
+impl LinkMetric {
+    /// Returns the value of this score for a candidate.
+    pub fn value(self, candidate: &Candidate) -> f64 {
+        match self {
+            LinkMetric::CommonNeighbours => candidate.common_neighbours as f64,
+            LinkMetric::Jaccard => candidate.jaccard,
+            LinkMetric::AdamicAdar => candidate.adamic_adar,
+            LinkMetric::PreferentialAttachment => candidate.preferential_attachment as f64,
+        }
+    }
+}
+
+impl fmt::Display for LinkMetric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            LinkMetric::CommonNeighbours => "common-neighbours",
+            LinkMetric::Jaccard => "jaccard",
+            LinkMetric::AdamicAdar => "adamic-adar",
+            LinkMetric::PreferentialAttachment => "preferential-attachment",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for LinkMetric {
+    type Err = String;
+
+    /// Parses a score by the name it is displayed with.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "common-neighbours" => Ok(LinkMetric::CommonNeighbours),
+            "jaccard" => Ok(LinkMetric::Jaccard),
+            "adamic-adar" => Ok(LinkMetric::AdamicAdar),
+            "preferential-attachment" => Ok(LinkMetric::PreferentialAttachment),
+            _ => Err(format!(
+                "unknown score `{}`; expected common-neighbours, jaccard, adamic-adar or \
+                 preferential-attachment",
+                text
+            )),
+        }
+    }
+}
+
+/// A pair of fighters who have not fought, and the scores of their matchup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    /// The node indexes of the two fighters, the smaller first.
+    pub fighters: (usize, usize),
+    pub common_neighbours: usize,
+    pub jaccard: f64,
+    pub adamic_adar: f64,
+    pub preferential_attachment: usize,
+}
+
+/// Scores every pair of fighters who have not fought, in node index order.
+///
+/// # Example
+///
+/// ```
+/// use graph_centrality_ufc::link_prediction::candidates;
+/// use petgraph::graph::UnGraph;
+///
+/// // Aldo and Diaz have both fought McGregor, but not each other.
+/// let graph = UnGraph::<&str, ()>::from_edges([(0, 1), (1, 2)]);
+/// let candidates = candidates(&graph);
+/// assert_eq!(candidates.len(), 1);
+/// assert_eq!(candidates[0].fighters, (0, 2));
+/// assert_eq!(candidates[0].common_neighbours, 1);
+/// ```
+pub fn candidates<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<Candidate> {
+    let adjacency = neighbours(graph);
+    let mut candidates = Vec::new();
+
+    for a in 0..adjacency.len() {
+        for b in a + 1..adjacency.len() {
+            // The neighbour lists are sorted, so membership is a binary search.
+            if adjacency[a].binary_search(&b).is_ok() {
+                continue;
+            }
+            let common: Vec<usize> = adjacency[a]
+                .iter()
+                .copied()
+                .filter(|z| adjacency[b].binary_search(z).is_ok())
+                .collect();
+            let union = adjacency[a].len() + adjacency[b].len() - common.len();
+            candidates.push(Candidate {
+                fighters: (a, b),
+                common_neighbours: common.len(),
+                jaccard: if union > 0 {
+                    common.len() as f64 / union as f64
+                } else {
+                    0.0
+                },
+                // A common neighbour has at least two neighbours, so the log is positive.
+                adamic_adar: common
+                    .iter()
+                    .map(|&z| 1.0 / (adjacency[z].len() as f64).ln())
+                    .sum(),
+                preferential_attachment: adjacency[a].len() * adjacency[b].len(),
+            });
+        }
+    }
+    candidates
+}
+
+/// Sorts candidates by a score, highest first.
+pub fn rank(candidates: &mut [Candidate], metric: LinkMetric) {
+    candidates.sort_by(|x, y| {
+        metric
+            .value(y)
+            .total_cmp(&metric.value(x))
+            .then(x.fighters.cmp(&y.fighters))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::graph::UnGraph;
+
+    #[test]
+    fn test_scores_and_ranking() {
+        // 0 and 1 have fought 2 and 3; 2 has also fought 4.
+        let graph = UnGraph::<(), ()>::from_edges([(0, 2), (0, 3), (1, 2), (1, 3), (2, 4)]);
+        let mut candidates = candidates(&graph);
+        // 10 pairs, less the 5 fights.
+        assert_eq!(candidates.len(), 5);
+
+        let pair = candidates.iter().find(|c| c.fighters == (0, 1)).unwrap();
+        assert_eq!(pair.common_neighbours, 2);
+        assert_eq!(pair.jaccard, 1.0);
+        assert!((pair.adamic_adar - (1.0 / 3f64.ln() + 1.0 / 2f64.ln())).abs() < 1e-12);
+        assert_eq!(pair.preferential_attachment, 4);
+
+        // 2 and 3 share the same two opponents, who have fewer opponents than 2.
+        rank(&mut candidates, LinkMetric::AdamicAdar);
+        assert_eq!(candidates[0].fighters, (2, 3));
+        assert!((candidates[0].adamic_adar - 2.0 / 2f64.ln()).abs() < 1e-12);
+        rank(&mut candidates, LinkMetric::Jaccard);
+        assert_eq!(candidates[0].fighters, (0, 1));
+        assert_eq!("jaccard".parse(), Ok(LinkMetric::Jaccard));
+    }
+}
@@ -2,68 +2,115 @@
 This program models a network of fighters using an undirected graph. Each fighter is represented as a node, and each fight between two fighters is represented as an edge.
 
 Key components:
-- The `Fighter` struct represents a fighter with a name.
+- The `Fighter` struct, from the `records` module, represents a fighter with a name and their record.
 - The `add_edge` function adds an edge between two nodes in the graph, indicating a fight between two fighters.
 - The `main` function initializes the graph, adds fighters as nodes, and establishes connections (fights) between them.
 
-The program calculates the closeness centrality for each fighter, which is defined here as the inverse of the number of fights (degree) a fighter has. It then prints the centrality values and provides a brief analysis of each fighter's centrality in the network.
+Given a fight-history CSV (`winner,loser,date,method,weight_class`), the program instead builds a
+directed graph from each winner to the loser, prints every fighter's record, and analyses that
+graph. The fights can be filtered by weight class and by date range:
 
-Closeness centrality in this context:
-- A lower centrality value indicates a higher number of fights.
-- A higher centrality value indicates fewer fights.
+cargo run -- data/ufc_fights.csv --weight-class Lightweight --from 2018-01-01 --to 2022-12-31
+
+For matchmaking, every pair of fighters who have not fought is scored by link prediction (common
+neighbours, Jaccard, Adamic-Adar and preferential attachment), and the most natural matchups are
+listed, ranked by the score chosen with `--rank-by`:
+
+cargo run -- data/ufc_fights.csv --matchups 5 --rank-by jaccard
+
+The program ranks the fighters by four centrality measures from the `centrality` module, printing a
+table for each and the fighters who lead it:
+- Degree: the share of the other fighters a fighter has faced.
+- Closeness (harmonic): how near a fighter is to everyone else, which stays defined when some
+  fighters cannot be reached.
+- Betweenness (Brandes): how often a fighter lies on the shortest paths between other fighters.
+- Eigenvector: how well connected a fighter's opponents are.
 
 The program uses the `petgraph` crate for graph operations and the `fmt` module for formatting output.
-The plotlib library is used to create an svg dot file of the graph for visualisation.
+With a fight-history file, the results are also replayed in date order to give every fighter an
+Elo and a Glicko-2 rating. The ratings are printed, and the plotlib library charts the Elo history
+of the highest-rated fighters to fighter_ratings.svg.
+
+The network itself is drawn to fighters_graph.svg: a Fruchterman-Reingold force-directed layout
+places the fighters, who are sized and coloured by the centrality measure chosen with
+`--centrality`, with winners pointing at losers when a fight-history file is given.
+The graph itself is exported with the `graph_export` crate to fighters_graph.dot for Graphviz and
+fighters_graph.gexf for Gephi, with each fighter sized by their number of fights.
 */
 
-use petgraph::dot::{Config, Dot};
-use petgraph::graph::{NodeIndex, UnGraph};
-use petgraph::Direction;
+use clap::Parser;
+use graph_centrality_ufc::centrality::{ranking, Centrality};
+use graph_centrality_ufc::layout::{write_svg, FruchtermanReingold};
+use graph_centrality_ufc::link_prediction::{candidates, rank, LinkMetric};
+use graph_centrality_ufc::ratings::{replay, Elo, FighterRatings, Glicko2};
+use graph_centrality_ufc::records::{
+    build_graph, load_fights, Date, FightFilter, FightGraph, Fighter,
+};
+use graph_export::{community_colour, hex_colour, ExportGraph};
+use petgraph::graph::{Graph, NodeIndex, UnGraph};
+use petgraph::EdgeType;
 use plotlib::page::Page;
 use plotlib::repr::Plot;
-use plotlib::style::PointMarker;
+use plotlib::style::{LineStyle, PointMarker, PointStyle};
 use plotlib::view::ContinuousView;
-use std::fmt;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
 
-#[derive(Debug)]
-struct Fighter {
-    name: String,
-}
+#[derive(Parser, Debug)]
+#[command(about = "Rank UFC fighters by centrality in their fight network")]
+struct Args {
+    /// A fight-history CSV with a winner,loser,date,method,weight_class header; without it a
+    /// small built-in network is analysed
+    fights: Option<PathBuf>,
 
-// Constructor for Fighter struct
-impl Fighter {
-    /// Creates a new Fighter with the given name.
-    ///
-    /// # Arguments
-    ///
-    /// * `name` - A string slice that holds the name of the fighter.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// let fighter = Fighter::new("Khabib Nurmagomedov");
-    /// ```
-    fn new(name: &str) -> Self {
-        Self {
-            name: name.to_string(),
-        }
-    }
+    /// Keep only the fights of this weight class
+    #[arg(short, long, requires = "fights")]
+    weight_class: Option<String>,
+
+    /// Keep only the fights on or after this date (YYYY-MM-DD)
+    #[arg(long, requires = "fights")]
+    from: Option<Date>,
+
+    /// Keep only the fights on or before this date (YYYY-MM-DD)
+    #[arg(long, requires = "fights")]
+    to: Option<Date>,
+
+    /// How many candidate matchups to list
+    #[arg(short, long, default_value_t = 10)]
+    matchups: usize,
+
+    /// Rank matchups by common-neighbours, jaccard, adamic-adar or preferential-attachment
+    #[arg(short, long, default_value_t = LinkMetric::AdamicAdar)]
+    rank_by: LinkMetric,
+
+    /// Size and colour the fighters in the network picture by degree, closeness, betweenness or
+    /// eigenvector centrality
+    #[arg(short, long, default_value_t = Centrality::Degree)]
+    centrality: Centrality,
+
+    /// How far a single result can move an Elo rating; must be above 0
+    #[arg(short, long, default_value_t = 32.0, requires = "fights", value_parser = parse_k_factor)]
+    k_factor: f64,
 }
 
-// Impl Display
-impl fmt::Display for Fighter {
-    /// Formats the fighter's name for display.
-    ///
-    /// # Arguments
-    ///
-    /// * `f` - A mutable reference to a formatter.
-    ///
-    /// # Returns
-    ///
-    /// * `fmt::Result` - The result of the formatting operation.
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.name)
+/// The number of highest-rated fighters whose rating history is charted.
+const CHART_FIGHTERS: usize = 5;
+
+/// Parses an Elo K-factor, which must be a finite number above 0.
+fn parse_k_factor(s: &str) -> Result<f64, String> {
+    let k_factor: f64 = s
+        .parse()
+        .map_err(|e| format!("invalid K-factor `{}`: {}", s, e))?;
+    if !k_factor.is_finite() || k_factor <= 0.0 {
+        return Err(format!(
+            "K-factor must be a finite number above 0, got {}",
+            k_factor
+        ));
     }
+    Ok(k_factor)
 }
 
 /// Adds an edge between two nodes in the graph.
@@ -84,7 +131,256 @@ fn add_edge(graph: &mut UnGraph<&Fighter, f32>, nodes: &[NodeIndex], a: usize, b
     graph.add_edge(nodes[a], nodes[b], 1.0);
 }
 
-fn main() {
+/// Prints the fighters' records, most wins first.
+///
+/// # Arguments
+///
+/// * `graph` - A reference to the win/loss graph.
+fn print_records(graph: &FightGraph) {
+    let mut fighters: Vec<&Fighter> = graph.node_weights().collect();
+    fighters.sort_by(|a, b| b.wins.cmp(&a.wins).then(a.losses.cmp(&b.losses)));
+    let width = name_width(fighters.iter().map(|fighter| fighter.name.len()));
+
+    println!("Records");
+    println!("{:<width$}  {:>4}  {:>6}", "Fighter", "Wins", "Losses");
+    for fighter in fighters {
+        println!(
+            "{:<width$}  {:>4}  {:>6}",
+            fighter.name, fighter.wins, fighter.losses
+        );
+    }
+    println!("-----------------");
+}
+
+/// Prints the fighters ranked by a centrality measure, and which fighters lead it.
+///
+/// # Arguments
+///
+/// * `graph` - A reference to the graph. Edge direction is ignored.
+/// * `measure` - The centrality measure to rank by.
+fn print_ranking<N: Display, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, measure: Centrality) {
+    let scores = measure.compute(graph);
+    let ranked = ranking(&scores);
+    let names: Vec<String> = graph.node_weights().map(ToString::to_string).collect();
+    let width = name_width(names.iter().map(String::len));
+
+    println!("{} centrality", capitalise(&measure.to_string()));
+    println!("{:>4}  {:<width$}  {:>7}", "Rank", "Fighter", "Score");
+    for (rank, &(node, score)) in ranked.iter().enumerate() {
+        println!("{:>4}  {:<width$}  {:>7.3}", rank + 1, names[node], score);
+    }
+
+    // The fighters tied for the highest score
+    if let Some(&(_, top)) = ranked.first() {
+        let leaders: Vec<&str> = ranked
+            .iter()
+            .take_while(|&&(_, score)| (top - score).abs() < 1e-9)
+            .map(|&(node, _)| names[node].as_str())
+            .collect();
+        println!(
+            "Highest {} centrality: {} ({:.3})",
+            measure,
+            leaders.join(", "),
+            top
+        );
+    }
+    println!("-----------------");
+}
+
+/// Prints the most natural matchups between fighters who have not fought, with every score.
+///
+/// # Arguments
+///
+/// * `graph` - A reference to the graph. Edge direction is ignored.
+/// * `metric` - The score to rank the matchups by.
+/// * `count` - How many matchups to print.
+fn print_matchups<N: Display, E, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    metric: LinkMetric,
+    count: usize,
+) {
+    let mut candidates = candidates(graph);
+    rank(&mut candidates, metric);
+    let names: Vec<String> = graph.node_weights().map(ToString::to_string).collect();
+    let width = name_width(names.iter().map(String::len));
+
+    println!("Candidate matchups by {}", metric);
+    println!(
+        "{:>4}  {:<width$}  {:<width$}  {:>6}  {:>7}  {:>11}  {:>11}",
+        "Rank", "Fighter", "Opponent", "Common", "Jaccard", "Adamic-Adar", "Pref. att."
+    );
+    for (rank, candidate) in candidates.iter().take(count).enumerate() {
+        let (a, b) = candidate.fighters;
+        println!(
+            "{:>4}  {:<width$}  {:<width$}  {:>6}  {:>7.3}  {:>11.3}  {:>11}",
+            rank + 1,
+            names[a],
+            names[b],
+            candidate.common_neighbours,
+            candidate.jaccard,
+            candidate.adamic_adar,
+            candidate.preferential_attachment
+        );
+    }
+    if candidates.is_empty() {
+        println!("Every fighter has fought every other fighter.");
+    }
+    println!("-----------------");
+}
+
+/// Ranks the fighters by every centrality measure, lists candidate matchups, draws the network
+/// and exports the graph.
+///
+/// # Arguments
+///
+/// * `graph` - A reference to the graph.
+/// * `args` - The command-line arguments.
+fn analyse<N: Display, E: Display, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    args: &Args,
+) -> Result<(), Box<dyn Error>> {
+    // Rank the fighters by each centrality measure
+    for measure in Centrality::ALL {
+        print_ranking(graph, measure);
+    }
+    print_matchups(graph, args.rank_by, args.matchups);
+
+    // Draw the network with a force-directed layout, sized and coloured by centrality
+    let positions = FruchtermanReingold::new().layout(graph);
+    let scores = args.centrality.compute(graph);
+    write_svg(
+        graph,
+        &positions,
+        &scores,
+        BufWriter::new(File::create("fighters_graph.svg")?),
+    )?;
+
+    let fights: Vec<f64> = graph
+        .node_indices()
+        .map(|node| graph.neighbors_undirected(node).count() as f64)
+        .collect();
+
+    // Export the graph for Graphviz and Gephi, sizing fighters by their number of fights
+    let export = ExportGraph::from_graph(graph).with_scores(fights)?;
+    for path in ["fighters_graph.dot", "fighters_graph.gexf"] {
+        export.save(path)?;
+    }
+    Ok(())
+}
+
+/// Prints the fighters' final Elo and Glicko-2 ratings, highest Elo first.
+///
+/// # Arguments
+///
+/// * `ratings` - The fighters' ratings.
+fn print_ratings(ratings: &[FighterRatings]) {
+    let mut ranked: Vec<&FighterRatings> = ratings.iter().collect();
+    ranked.sort_by(|a, b| b.elo.total_cmp(&a.elo));
+    let width = name_width(ranked.iter().map(|fighter| fighter.name.len()));
+
+    println!("Ratings");
+    println!(
+        "{:>4}  {:<width$}  {:>6}  {:>7}  {:>5}  {:>10}  {:>6}",
+        "Rank", "Fighter", "Elo", "Glicko", "RD", "Volatility", "Fights"
+    );
+    for (rank, fighter) in ranked.iter().enumerate() {
+        println!(
+            "{:>4}  {:<width$}  {:>6.0}  {:>7.0}  {:>5.0}  {:>10.4}  {:>6}",
+            rank + 1,
+            fighter.name,
+            fighter.elo,
+            fighter.glicko.rating,
+            fighter.glicko.deviation,
+            fighter.glicko.volatility,
+            fighter.history.len()
+        );
+    }
+    println!("-----------------");
+}
+
+/// Charts the Elo rating history of the highest-rated fighters as lines over time.
+///
+/// # Arguments
+///
+/// * `ratings` - The fighters' ratings.
+/// * `path` - The SVG file to save the chart to.
+fn chart_ratings(ratings: &[FighterRatings], path: &str) -> Result<(), Box<dyn Error>> {
+    let mut ranked: Vec<&FighterRatings> = ratings.iter().collect();
+    ranked.sort_by(|a, b| b.elo.total_cmp(&a.elo));
+
+    let mut view = ContinuousView::new().x_label("Year").y_label("Elo rating");
+    for (i, fighter) in ranked.iter().take(CHART_FIGHTERS).enumerate() {
+        let colour = hex_colour(community_colour(i));
+        let history = fighter
+            .history
+            .iter()
+            .map(|point| (point.date.as_year(), point.elo))
+            .collect();
+        let line = Plot::new(history)
+            .legend(fighter.name.clone())
+            .line_style(LineStyle::new().colour(colour.as_str()))
+            .point_style(
+                PointStyle::new()
+                    .marker(PointMarker::Circle)
+                    .colour(colour.as_str()),
+            );
+        view = view.add(line);
+    }
+
+    Page::single(&view).save(path)?;
+    Ok(())
+}
+
+// The width of the fighter column: the longest name, or the column header.
+fn name_width<I: Iterator<Item = usize>>(lengths: I) -> usize {
+    lengths.max().unwrap_or(0).max("Fighter".len())
+}
+
+fn capitalise(text: &str) -> String {
+    let mut chars = text.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    if let Some(path) = &args.fights {
+        let fights = load_fights(path)?;
+        let mut filter = FightFilter::new();
+        if let Some(weight_class) = &args.weight_class {
+            filter = filter.with_weight_class(weight_class);
+        }
+        if let Some(from) = args.from {
+            filter = filter.with_from(from);
+        }
+        if let Some(to) = args.to {
+            filter = filter.with_to(to);
+        }
+        let kept = filter.apply(&fights);
+        if kept.is_empty() {
+            return Err(format!("no fights in {} match the filters", path.display()).into());
+        }
+
+        let graph = build_graph(&kept);
+        println!(
+            "Loaded {} of {} fights between {} fighters from {}",
+            kept.len(),
+            fights.len(),
+            graph.node_count(),
+            path.display()
+        );
+        println!("-----------------");
+        print_records(&graph);
+
+        let elo = Elo::new().with_k_factor(args.k_factor);
+        let ratings = replay(&kept, &elo, &Glicko2::new());
+        print_ratings(&ratings);
+        chart_ratings(&ratings, "fighter_ratings.svg")?;
+        return analyse(&graph, &args);
+    }
+
     // Create an undirected graph
     let mut graph = UnGraph::new_undirected();
 
@@ -112,51 +408,20 @@ fn main() {
     add_edge(&mut graph, &fighter_nodes, 0, 4); // Dustin Poirier vs. Nate Diaz
     add_edge(&mut graph, &fighter_nodes, 2, 4); // Jose Aldo vs. Nate Diaz
 
-    // Calculate the centrality
-    for (i, &node) in fighter_nodes.iter().enumerate() {
-        let name = &fighters[i].name;
-        let degree = graph.edges_directed(node, Direction::Outgoing).count() as f32;
-        let closeness = 1.0 / degree;
-        println!("The closeness centrality of {} is {:.2}", name, closeness);
-
-        match name.as_str() {
-            "Conor McGregor" => println!(
-                "{} has the lowest centrality because he has fought with all other fighters in the network. In this context, a lower centrality value means a higher number of fights.",
-                name
-            ),
-            "Dustin Poirier" | "Nate Diaz" => println!(
-                "{} has a centrality of {:.2}, implying they had less fights compared to Conor McGregor but more than Khabib Nurmagomedov and Jose Aldo.",
-                name, closeness
-            ),
-            "Khabib Nurmagomedov" | "Jose Aldo" => println!(
-                "{} has the highest centrality of {:.2} as they have fought with the least number of fighters.",
-                name, closeness
-            ),
-            _ => {}
-        }
-        println!("-----------------");
-    }
-
-    // Prepare data for visualization
-    let mut data = Vec::new();
-    for (i, &node) in fighter_nodes.iter().enumerate() {
-        let degree = graph.edges_directed(node, Direction::Outgoing).count() as f64;
-        data.push((i as f64, degree));
-    }
-
-    // Create a scatter plot
-    let s = Plot::new(data).point_style(
-        plotlib::style::PointStyle::new()
-            .marker(PointMarker::Square)
-            .colour("#DD3355"),
-    );
+    analyse(&graph, &args)
+}
 
-    // Create a view and add the scatter plot to it
-    let v = ContinuousView::new()
-        .add(s)
-        .x_label("Fighter Index")
-        .y_label("Number of Fights");
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // Save the plot to an SVG file
-    Page::single(&v).save("fighters_graph.svg").unwrap();
+    #[test]
+    fn test_k_factor_range() {
+        for valid in ["32", "0.5"] {
+            assert!(parse_k_factor(valid).is_ok(), "{}", valid);
+        }
+        for invalid in ["0", "-16", "NaN", "inf", "big"] {
+            assert!(parse_k_factor(invalid).is_err(), "{}", invalid);
+        }
+    }
 }
@@ -0,0 +1,342 @@
+/* Elo and Glicko-2 ratings replayed from fight results.
+
+Fights are replayed in date order; fights on the same date keep their order in
+the records. Each fight updates both fighters from their ratings before it:
+
+- Elo moves a rating by the K-factor times the difference between the result
+  (1 for a win, 0 for a loss) and the expected result,
+  1 / (1 + 10^((opponent - rating) / 400)).
+- Glicko-2 also tracks how uncertain each rating is (the rating deviation) and
+  how erratic the fighter's results are (the volatility). Every fight is its own
+  rating period, so a surprising result moves an uncertain rating further, and
+  the deviation shrinks as a fighter's results come in. The updates follow
+  Glickman's "Example of the Glicko-2 system".
+
+Every fighter's ratings after each of their fights are kept as a history, for
+charting.
+*/
+
+use crate::records::{Date, Fight};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// The Glicko-2 scale factor between the Glicko and Glicko-2 rating scales.
+const GLICKO2_SCALE: f64 = 173.7178;
+
+/// The convergence tolerance of the Glicko-2 volatility iteration.
+const VOLATILITY_TOLERANCE: f64 = 1e-6;
+
+/// The Elo rating system.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Elo {
+    k_factor: f64,
+    initial: f64,
+}
+
+impl Default for Elo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Elo {
+    /// Creates an Elo system with a K-factor of 32 and an initial rating of 1500.
+    pub fn new() -> Self {
+        Self {
+            k_factor: 32.0,
+            initial: 1500.0,
+        }
+    }
+
+    /// Sets how far a single result can move a rating.
+    pub fn with_k_factor(mut self, k_factor: f64) -> Self {
+        self.k_factor = k_factor;
+        self
+    }
+
+    /// Sets the rating of a fighter before their first fight.
+    pub fn with_initial(mut self, initial: f64) -> Self {
+        self.initial = initial;
+        self
+    }
+
+    /// Returns the expected result, between 0 and 1, of a fighter against an opponent.
+    pub fn expected(&self, rating: f64, opponent: f64) -> f64 {
+        1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+    }
+
+    /// Returns a fighter's new rating after a result: 1 for a win, 0 for a loss.
+    pub fn update(&self, rating: f64, opponent: f64, result: f64) -> f64 {
+        rating + self.k_factor * (result - self.expected(rating, opponent))
+    }
+}
+
+/// A Glicko-2 rating, on the Glicko scale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlickoRating {
+    pub rating: f64,
+    /// The rating deviation: how uncertain the rating is.
+    pub deviation: f64,
+    /// How much the fighter's performance fluctuates.
+    pub volatility: f64,
+}
+
+/// The Glicko-2 rating system.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glicko2 {
+    tau: f64,
+    initial: GlickoRating,
+}
+
+impl Default for Glicko2 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Glicko2 {
+    /// Creates a Glicko-2 system with a system constant (tau) of 0.5, and an
+    /// initial rating of 1500 with a deviation of 350 and a volatility of 0.06.
+    pub fn new() -> Self {
+        Self {
+            tau: 0.5,
+            initial: GlickoRating {
+                rating: 1500.0,
+                deviation: 350.0,
+                volatility: 0.06,
+            },
+        }
+    }
+
+    /// Sets the system constant, which limits how fast the volatility can change.
+    pub fn with_tau(mut self, tau: f64) -> Self {
+        self.tau = tau;
+        self
+    }
+
+    /// Sets the rating of a fighter before their first fight.
+    pub fn with_initial(mut self, initial: GlickoRating) -> Self {
+        self.initial = initial;
+        self
+    }
+
+    /// Returns a fighter's new rating after one rating period with the given
+    /// results against opponents: 1 for a win, 0 for a loss. Without results,
+    /// only the deviation grows.
+    pub fn update(&self, player: GlickoRating, results: &[(GlickoRating, f64)]) -> GlickoRating {
+        let mu = (player.rating - 1500.0) / GLICKO2_SCALE;
+        let phi = player.deviation / GLICKO2_SCALE;
+        let sigma = player.volatility;
+
+        if results.is_empty() {
+            return GlickoRating {
+                deviation: (phi * phi + sigma * sigma).sqrt() * GLICKO2_SCALE,
+                ..player
+            };
+        }
+
+        // The estimated variance of the rating from the results alone, and the
+        // estimated improvement.
+        let mut inverse_variance = 0.0;
+        let mut improvement = 0.0;
+        for (opponent, result) in results {
+            let mu_j = (opponent.rating - 1500.0) / GLICKO2_SCALE;
+            let g = g(opponent.deviation / GLICKO2_SCALE);
+            let expected = 1.0 / (1.0 + (-g * (mu - mu_j)).exp());
+            inverse_variance += g * g * expected * (1.0 - expected);
+            improvement += g * (result - expected);
+        }
+        let v = 1.0 / inverse_variance;
+        let delta = v * improvement;
+
+        let sigma = self.volatility(phi, sigma, v, delta);
+        let phi_star = (phi * phi + sigma * sigma).sqrt();
+        let phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / v).sqrt();
+        let mu = mu + phi * phi * improvement;
+
+        GlickoRating {
+            rating: mu * GLICKO2_SCALE + 1500.0,
+            deviation: phi * GLICKO2_SCALE,
+            volatility: sigma,
+        }
+    }
+
+    // The new volatility, found with the Illinois algorithm.
+    fn volatility(&self, phi: f64, sigma: f64, v: f64, delta: f64) -> f64 {
+        let a = (sigma * sigma).ln();
+        let tau = self.tau;
+        let f = |x: f64| {
+            let ex = x.exp();
+            let denominator = phi * phi + v + ex;
+            ex * (delta * delta - phi * phi - v - ex) / (2.0 * denominator * denominator)
+                - (x - a) / (tau * tau)
+        };
+
+        let mut lower = a;
+        let mut upper = if delta * delta > phi * phi + v {
+            (delta * delta - phi * phi - v).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * tau) < 0.0 {
+                k += 1.0;
+            }
+            a - k * tau
+        };
+        let (mut f_lower, mut f_upper) = (f(lower), f(upper));
+        while (upper - lower).abs() > VOLATILITY_TOLERANCE {
+            let next = lower + (lower - upper) * f_lower / (f_upper - f_lower);
+            let f_next = f(next);
+            if f_next * f_upper <= 0.0 {
+                lower = upper;
+                f_lower = f_upper;
+            } else {
+                f_lower /= 2.0;
+            }
+            upper = next;
+            f_upper = f_next;
+        }
+        (lower / 2.0).exp()
+    }
+}
+
+// Reduces the weight of a result against an opponent with an uncertain rating.
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt()
+}
+
+/// A fighter's ratings after one of their fights.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RatingPoint {
+    pub date: Date,
+    pub elo: f64,
+    pub glicko: GlickoRating,
+}
+
+/// A fighter's final ratings and how they got there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FighterRatings {
+    pub name: String,
+    pub elo: f64,
+    pub glicko: GlickoRating,
+    /// The ratings after each fight, oldest first.
+    pub history: Vec<RatingPoint>,
+}
+
+/// Replays the fights in date order and rates every fighter. The fighters are
+/// listed in order of first appearance in the records, as in
+/// [`build_graph`](crate::records::build_graph), so they line up with its nodes.
+///
+/// # Example
+///
+/// ```
+/// use graph_centrality_ufc::ratings::{replay, Elo, Glicko2};
+/// use graph_centrality_ufc::records::read_csv;
+///
+/// let csv = "winner,loser,date,method,weight_class\n\
+///            Conor McGregor,Jose Aldo,2015-12-12,KO,Featherweight\n";
+/// let fights = read_csv(csv.as_bytes()).unwrap();
+/// let ratings = replay(&fights, &Elo::new(), &Glicko2::new());
+/// assert_eq!(ratings[0].elo, 1516.0);
+/// assert_eq!(ratings[1].elo, 1484.0);
+/// ```
+pub fn replay(fights: &[Fight], elo: &Elo, glicko: &Glicko2) -> Vec<FighterRatings> {
+    let mut fighters: Vec<FighterRatings> = Vec::new();
+    let mut indexes = HashMap::new();
+    for fight in fights {
+        for name in [&fight.winner, &fight.loser] {
+            indexes.entry(name.as_str()).or_insert_with(|| {
+                fighters.push(FighterRatings {
+                    name: name.clone(),
+                    elo: elo.initial,
+                    glicko: glicko.initial,
+                    history: Vec::new(),
+                });
+                fighters.len() - 1
+            });
+        }
+    }
+
+    // A stable sort keeps same-day fights in their order in the records.
+    let mut order: Vec<&Fight> = fights.iter().collect();
+    order.sort_by_key(|fight| fight.date);
+
+    for fight in order {
+        let (w, l) = (
+            indexes[fight.winner.as_str()],
+            indexes[fight.loser.as_str()],
+        );
+        let (winner, loser) = (&fighters[w], &fighters[l]);
+        let elo_ratings = (
+            elo.update(winner.elo, loser.elo, 1.0),
+            elo.update(loser.elo, winner.elo, 0.0),
+        );
+        let glicko_ratings = (
+            glicko.update(winner.glicko, &[(loser.glicko, 1.0)]),
+            glicko.update(loser.glicko, &[(winner.glicko, 0.0)]),
+        );
+
+        for (index, elo, glicko) in [
+            (w, elo_ratings.0, glicko_ratings.0),
+            (l, elo_ratings.1, glicko_ratings.1),
+        ] {
+            let fighter = &mut fighters[index];
+            fighter.elo = elo;
+            fighter.glicko = glicko;
+            fighter.history.push(RatingPoint {
+                date: fight.date,
+                elo,
+                glicko,
+            });
+        }
+    }
+    fighters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::records::read_csv;
+
+    fn rating(rating: f64, deviation: f64) -> GlickoRating {
+        GlickoRating {
+            rating,
+            deviation,
+            volatility: 0.06,
+        }
+    }
+
+    #[test]
+    fn test_glicko2_matches_glickmans_example() {
+        let player = rating(1500.0, 200.0);
+        let results = [
+            (rating(1400.0, 30.0), 1.0),
+            (rating(1550.0, 100.0), 0.0),
+            (rating(1700.0, 300.0), 0.0),
+        ];
+        let updated = Glicko2::new().update(player, &results);
+
+        assert!((updated.rating - 1464.06).abs() < 0.01);
+        assert!((updated.deviation - 151.52).abs() < 0.01);
+        assert!((updated.volatility - 0.05999).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_replay_runs_in_date_order() {
+        // The rematch is listed first but happened later.
+        let csv = "winner,loser,date,method,weight_class
+Conor McGregor,Nate Diaz,2016-08-20,Decision,Welterweight
+Nate Diaz,Conor McGregor,2016-03-05,Submission,Welterweight
+";
+        let fights = read_csv(csv.as_bytes()).unwrap();
+        let ratings = replay(&fights, &Elo::new(), &Glicko2::new());
+
+        let conor = &ratings[0];
+        assert_eq!(conor.name, "Conor McGregor");
+        assert_eq!(conor.history[0].date, "2016-03-05".parse().unwrap());
+        assert_eq!(conor.history[0].elo, 1484.0);
+        // Beating a higher-rated opponent gains more than the 16 points first lost.
+        assert!(conor.elo > 1500.0);
+        assert!(conor.glicko.deviation < 350.0);
+        assert!((conor.elo + ratings[1].elo - 3000.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,335 @@
+/* Loading fight records into a directed win/loss graph.
+
+Fight histories are CSV files with a header row, one fight per row:
+```text
+winner,loser,date,method,weight_class
+Conor McGregor,Jose Aldo,2015-12-12,KO,Featherweight
+```
+Dates are written as YYYY-MM-DD. In the graph, every fight is an edge from the
+winner to the loser, so rematches are kept as separate edges, and every fighter
+carries their number of wins and losses in the loaded fights.
+
+A `FightFilter` keeps only the fights of one weight class, or within a date
+range, before the graph is built.
+*/
+
+use petgraph::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A directed graph of fighters, with an edge from the winner to the loser of each fight.
+pub type FightGraph = DiGraph<Fighter, Fight>;
+
+/// A fighter and their record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fighter {
+    pub name: String,
+    pub wins: usize,
+    pub losses: usize,
+}
+
+impl Fighter {
+    /// Creates a new Fighter with the given name and no fights.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A string slice that holds the name of the fighter.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graph_centrality_ufc::records::Fighter;
+    ///
+    /// let fighter = Fighter::new("Khabib Nurmagomedov");
+    /// assert_eq!(fighter.wins, 0);
+    /// ```
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            wins: 0,
+            losses: 0,
+        }
+    }
+}
+
+impl fmt::Display for Fighter {
+    /// Formats the fighter's name for display.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// A calendar date, ordered from the earliest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Date {
+    pub year: u32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    /// Parses a date written as YYYY-MM-DD.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid date `{}`; expected YYYY-MM-DD", text);
+        let parts: Vec<&str> = text.trim().split('-').collect();
+        let [year, month, day] = parts.as_slice() else {
+            return Err(invalid());
+        };
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return Err(invalid());
+        }
+        let date = Date {
+            year: year.parse().map_err(|_| invalid())?,
+            month: month.parse().map_err(|_| invalid())?,
+            day: day.parse().map_err(|_| invalid())?,
+        };
+        if !(1..=12).contains(&date.month) || date.day < 1 || date.day > date.days_in_month() {
+            return Err(invalid());
+        }
+        Ok(date)
+    }
+}
+
+impl TryFrom<String> for Date {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl Date {
+    /// Returns the date as a fractional year, such as 2015.95 for 2015-12-12, for plotting.
+    pub fn as_year(&self) -> f64 {
+        let day_of_year: u32 = (1..self.month)
+            .map(|month| self.days_in_month_of(month))
+            .sum::<u32>()
+            + self.day
+            - 1;
+        let days = if self.days_in_month_of(2) == 29 {
+            366
+        } else {
+            365
+        };
+        self.year as f64 + day_of_year as f64 / days as f64
+    }
+
+    fn days_in_month_of(&self, month: u32) -> u32 {
+        Date { month, ..*self }.days_in_month()
+    }
+
+    fn days_in_month(&self) -> u32 {
+        match self.month {
+            2 if self.year.is_multiple_of(4)
+                && (!self.year.is_multiple_of(100) || self.year.is_multiple_of(400)) =>
+            {
+                29
+            }
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+}
+
+/// One fight and its outcome.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Fight {
+    pub winner: String,
+    pub loser: String,
+    pub date: Date,
+    /// How the fight was won, e.g. `KO`, `Submission` or `Decision`.
+    pub method: String,
+    pub weight_class: String,
+}
+
+impl fmt::Display for Fight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} ({})", self.method, self.date, self.weight_class)
+    }
+}
+
+/// Which fights to keep: those of one weight class, and those between two dates,
+/// inclusive. An unset bound keeps every fight.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FightFilter {
+    weight_class: Option<String>,
+    from: Option<Date>,
+    to: Option<Date>,
+}
+
+impl FightFilter {
+    /// Creates a filter that keeps every fight.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps only the fights of a weight class, ignoring case.
+    pub fn with_weight_class(mut self, weight_class: &str) -> Self {
+        self.weight_class = Some(weight_class.to_string());
+        self
+    }
+
+    /// Keeps only the fights on or after a date.
+    pub fn with_from(mut self, from: Date) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// Keeps only the fights on or before a date.
+    pub fn with_to(mut self, to: Date) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    /// Returns whether the filter keeps a fight.
+    pub fn matches(&self, fight: &Fight) -> bool {
+        self.weight_class
+            .as_ref()
+            .is_none_or(|class| class.eq_ignore_ascii_case(&fight.weight_class))
+            && self.from.is_none_or(|from| fight.date >= from)
+            && self.to.is_none_or(|to| fight.date <= to)
+    }
+
+    /// Returns the fights the filter keeps, in their original order.
+    pub fn apply(&self, fights: &[Fight]) -> Vec<Fight> {
+        fights
+            .iter()
+            .filter(|fight| self.matches(fight))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Reads fights from a CSV file. See [`read_csv`].
+///
+/// # Example
+///
+/// ```no_run
+/// use graph_centrality_ufc::records::{build_graph, load_fights, FightFilter};
+///
+/// let fights = load_fights("data/ufc_fights.csv").unwrap();
+/// let lightweight = FightFilter::new().with_weight_class("Lightweight").apply(&fights);
+/// let graph = build_graph(&lightweight);
+/// assert_eq!(graph.edge_count(), lightweight.len());
+/// ```
+pub fn load_fights<P: AsRef<Path>>(path: P) -> Result<Vec<Fight>, Box<dyn Error>> {
+    read_csv(File::open(path)?)
+}
+
+/// Reads fights from CSV with a `winner,loser,date,method,weight_class` header.
+pub fn read_csv<R: Read>(reader: R) -> Result<Vec<Fight>, Box<dyn Error>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let mut fights = Vec::new();
+
+    for (line, result) in rdr.deserialize().enumerate() {
+        let fight: Fight = result.map_err(|e| format!("fight record {}: {}", line + 1, e))?;
+        if fight.winner.is_empty() || fight.loser.is_empty() {
+            return Err(format!("fight record {} must have a winner and a loser", line + 1).into());
+        }
+        if fight.winner == fight.loser {
+            return Err(format!(
+                "fight record {}: {} cannot fight themselves",
+                line + 1,
+                fight.winner
+            )
+            .into());
+        }
+        fights.push(fight);
+    }
+
+    Ok(fights)
+}
+
+/// Builds the win/loss graph, adding each fighter once, in order of first
+/// appearance, and one edge per fight from the winner to the loser.
+pub fn build_graph(fights: &[Fight]) -> FightGraph {
+    let mut graph = FightGraph::new();
+    let mut nodes = HashMap::new();
+
+    for fight in fights {
+        let mut node = |name: &str| {
+            *nodes
+                .entry(name.to_string())
+                .or_insert_with(|| graph.add_node(Fighter::new(name)))
+        };
+        let winner = node(&fight.winner);
+        let loser = node(&fight.loser);
+        graph[winner].wins += 1;
+        graph[loser].losses += 1;
+        graph.add_edge(winner, loser, fight.clone());
+    }
+
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIGHTS: &str = "winner,loser,date,method,weight_class
+Conor McGregor,Jose Aldo,2015-12-12,KO,Featherweight
+Nate Diaz,Conor McGregor,2016-03-05,Submission,Welterweight
+Conor McGregor,Nate Diaz,2016-08-20,Decision,Welterweight
+Khabib Nurmagomedov,Conor McGregor,2018-10-06,Submission,Lightweight
+";
+
+    #[test]
+    fn test_records_build_a_win_loss_graph() {
+        let fights = read_csv(FIGHTS.as_bytes()).unwrap();
+        let graph = build_graph(&fights);
+
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.edge_count(), 4);
+        let conor = &graph[NodeIndex::new(0)];
+        assert_eq!(
+            (conor.name.as_str(), conor.wins, conor.losses),
+            ("Conor McGregor", 2, 2)
+        );
+        let first = graph.edge_references().next().unwrap();
+        assert_eq!(graph[first.target()].name, "Jose Aldo");
+    }
+
+    #[test]
+    fn test_filters() {
+        let fights = read_csv(FIGHTS.as_bytes()).unwrap();
+
+        let welterweight = FightFilter::new().with_weight_class("welterweight");
+        assert_eq!(welterweight.apply(&fights).len(), 2);
+
+        let in_2016 = FightFilter::new()
+            .with_from("2016-01-01".parse().unwrap())
+            .with_to("2016-12-31".parse().unwrap());
+        let kept = in_2016.apply(&fights);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0].winner, "Nate Diaz");
+    }
+
+    #[test]
+    fn test_invalid_dates_are_rejected() {
+        assert!("2016-02-29".parse::<Date>().is_ok());
+        assert!("2015-02-29".parse::<Date>().is_err());
+        assert!("12/12/2015".parse::<Date>().is_err());
+
+        let csv = "winner,loser,date,method,weight_class\na,b,2015-13-01,KO,Flyweight\n";
+        let error = read_csv(csv.as_bytes()).unwrap_err();
+        assert!(error.to_string().contains("fight record 1"));
+    }
+}
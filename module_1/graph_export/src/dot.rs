@@ -0,0 +1,57 @@
+// Writes graphs in Graphviz's DOT language.
+
+use crate::{hex_colour, node_colour, ExportGraph};
+use std::io::{self, Write};
+
+// Node widths in inches, for the lowest and highest scores.
+const MIN_WIDTH: f64 = 0.5;
+const MAX_WIDTH: f64 = 1.5;
+
+pub(crate) fn write<W: Write>(graph: &ExportGraph, mut w: W) -> io::Result<()> {
+    let (keyword, arrow) = if graph.directed {
+        ("digraph", "->")
+    } else {
+        ("graph", "--")
+    };
+    writeln!(w, "{} {{", keyword)?;
+
+    for ((index, node), relative_score) in
+        graph.nodes.iter().enumerate().zip(graph.relative_scores())
+    {
+        let mut attributes = vec![format!("label={}", quote(&node.label))];
+        if let Some(community) = node.community {
+            attributes.push(format!("community={}", community));
+        }
+        if let Some(score) = node.score {
+            attributes.push(format!("score={}", score));
+        }
+        attributes.push("style=filled".to_string());
+        attributes.push(format!(
+            "fillcolor={}",
+            quote(&hex_colour(node_colour(node)))
+        ));
+        attributes.push(format!(
+            "width={}",
+            MIN_WIDTH + (MAX_WIDTH - MIN_WIDTH) * relative_score
+        ));
+        writeln!(w, "    {} [{}]", index, attributes.join(", "))?;
+    }
+
+    for edge in &graph.edges {
+        writeln!(
+            w,
+            "    {} {} {} [label={}]",
+            edge.source,
+            arrow,
+            edge.target,
+            quote(&edge.label)
+        )?;
+    }
+
+    writeln!(w, "}}")
+}
+
+// Quotes a DOT string, escaping quotes and backslashes.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
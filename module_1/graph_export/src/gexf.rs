@@ -0,0 +1,79 @@
+// Writes graphs as GEXF 1.3, Gephi's native format, with viz colours and sizes.
+
+use crate::{escape_xml, node_colour, size, ExportGraph};
+use std::io::{self, Write};
+
+pub(crate) fn write<W: Write>(graph: &ExportGraph, mut w: W) -> io::Result<()> {
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        w,
+        r#"<gexf xmlns="http://gexf.net/1.3" xmlns:viz="http://gexf.net/1.3/viz" version="1.3">"#
+    )?;
+    let direction = if graph.directed {
+        "directed"
+    } else {
+        "undirected"
+    };
+    writeln!(
+        w,
+        r#"  <graph mode="static" defaultedgetype="{}">"#,
+        direction
+    )?;
+    writeln!(w, r#"    <attributes class="node">"#)?;
+    writeln!(
+        w,
+        r#"      <attribute id="community" title="community" type="integer"/>"#
+    )?;
+    writeln!(
+        w,
+        r#"      <attribute id="score" title="score" type="double"/>"#
+    )?;
+    writeln!(w, "    </attributes>")?;
+
+    writeln!(w, "    <nodes>")?;
+    for ((index, node), relative_score) in
+        graph.nodes.iter().enumerate().zip(graph.relative_scores())
+    {
+        writeln!(
+            w,
+            r#"      <node id="{}" label="{}">"#,
+            index,
+            escape_xml(&node.label)
+        )?;
+        if node.community.is_some() || node.score.is_some() {
+            writeln!(w, "        <attvalues>")?;
+            if let Some(community) = node.community {
+                writeln!(
+                    w,
+                    r#"          <attvalue for="community" value="{}"/>"#,
+                    community
+                )?;
+            }
+            if let Some(score) = node.score {
+                writeln!(w, r#"          <attvalue for="score" value="{}"/>"#, score)?;
+            }
+            writeln!(w, "        </attvalues>")?;
+        }
+        let (r, g, b) = node_colour(node);
+        writeln!(w, r#"        <viz:color r="{}" g="{}" b="{}"/>"#, r, g, b)?;
+        writeln!(w, r#"        <viz:size value="{}"/>"#, size(relative_score))?;
+        writeln!(w, "      </node>")?;
+    }
+    writeln!(w, "    </nodes>")?;
+
+    writeln!(w, "    <edges>")?;
+    for (index, edge) in graph.edges.iter().enumerate() {
+        writeln!(
+            w,
+            r#"      <edge id="{}" source="{}" target="{}" label="{}"/>"#,
+            index,
+            edge.source,
+            edge.target,
+            escape_xml(&edge.label)
+        )?;
+    }
+    writeln!(w, "    </edges>")?;
+
+    writeln!(w, "  </graph>")?;
+    writeln!(w, "</gexf>")
+}
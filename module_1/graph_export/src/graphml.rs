@@ -0,0 +1,80 @@
+// Writes graphs as GraphML, with the colour and size attributes Gephi reads.
+
+use crate::{escape_xml, node_colour, size, ExportGraph};
+use std::io::{self, Write};
+
+pub(crate) fn write<W: Write>(graph: &ExportGraph, mut w: W) -> io::Result<()> {
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        w,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+    )?;
+    for (id, domain, kind) in [
+        ("label", "node", "string"),
+        ("community", "node", "int"),
+        ("score", "node", "double"),
+        ("r", "node", "int"),
+        ("g", "node", "int"),
+        ("b", "node", "int"),
+        ("size", "node", "double"),
+        ("edge_label", "edge", "string"),
+    ] {
+        let name = if id == "edge_label" { "label" } else { id };
+        writeln!(
+            w,
+            r#"  <key id="{}" for="{}" attr.name="{}" attr.type="{}"/>"#,
+            id, domain, name, kind
+        )?;
+    }
+
+    let direction = if graph.directed {
+        "directed"
+    } else {
+        "undirected"
+    };
+    writeln!(w, r#"  <graph id="G" edgedefault="{}">"#, direction)?;
+
+    for ((index, node), relative_score) in
+        graph.nodes.iter().enumerate().zip(graph.relative_scores())
+    {
+        writeln!(w, r#"    <node id="n{}">"#, index)?;
+        writeln!(
+            w,
+            r#"      <data key="label">{}</data>"#,
+            escape_xml(&node.label)
+        )?;
+        if let Some(community) = node.community {
+            writeln!(w, r#"      <data key="community">{}</data>"#, community)?;
+        }
+        if let Some(score) = node.score {
+            writeln!(w, r#"      <data key="score">{}</data>"#, score)?;
+        }
+        let (r, g, b) = node_colour(node);
+        writeln!(w, r#"      <data key="r">{}</data>"#, r)?;
+        writeln!(w, r#"      <data key="g">{}</data>"#, g)?;
+        writeln!(w, r#"      <data key="b">{}</data>"#, b)?;
+        writeln!(
+            w,
+            r#"      <data key="size">{}</data>"#,
+            size(relative_score)
+        )?;
+        writeln!(w, "    </node>")?;
+    }
+
+    for (index, edge) in graph.edges.iter().enumerate() {
+        writeln!(
+            w,
+            r#"    <edge id="e{}" source="n{}" target="n{}">"#,
+            index, edge.source, edge.target
+        )?;
+        writeln!(
+            w,
+            r#"      <data key="edge_label">{}</data>"#,
+            escape_xml(&edge.label)
+        )?;
+        writeln!(w, "    </edge>")?;
+    }
+
+    writeln!(w, "  </graph>")?;
+    writeln!(w, "</graphml>")
+}
@@ -0,0 +1,336 @@
+/* Exporting graphs to DOT, GraphML and GEXF for Graphviz and Gephi.
+
+A petgraph graph is first turned into an `ExportGraph`, which can carry a
+community id and a score, such as a centrality, for every node. When the graph
+is written, nodes are coloured by community and sized by score:
+
+- DOT, for Graphviz: `fillcolor` and `width` attributes;
+- GraphML: `r`, `g`, `b` and `size` attributes, which Gephi reads as colour and size;
+- GEXF, Gephi's own format: `viz:color` and `viz:size` elements.
+
+The community id and score are also written as plain attributes, so they can be
+used for filtering in Gephi. `save` picks the format from the file extension.
+
+Usage:
+
+```no_run
+use graph_export::ExportGraph;
+use petgraph::graph::UnGraph;
+
+let mut graph = UnGraph::<&str, &str>::new_undirected();
+let aldo = graph.add_node("Jose Aldo");
+let diaz = graph.add_node("Nate Diaz");
+let poirier = graph.add_node("Dustin Poirier");
+graph.add_edge(aldo, diaz, "fought");
+graph.add_edge(diaz, poirier, "fought");
+
+ExportGraph::from_graph(&graph)
+    .with_communities(vec![0, 0, 1])
+    .and_then(|export| export.with_scores(vec![1.0, 2.0, 1.0]))
+    .and_then(|export| export.save("fighters.gexf"))
+    .unwrap();
+```
+*/
+
+mod dot;
+mod gexf;
+mod graphml;
+
+use petgraph::graph::Graph;
+use petgraph::visit::EdgeRef;
+use petgraph::EdgeType;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Node colours for the first communities; later communities get generated colours.
+const PALETTE: [(u8, u8, u8); 10] = [
+    (0x1f, 0x77, 0xb4),
+    (0xff, 0x7f, 0x0e),
+    (0x2c, 0xa0, 0x2c),
+    (0xd6, 0x27, 0x28),
+    (0x94, 0x67, 0xbd),
+    (0x8c, 0x56, 0x4b),
+    (0xe3, 0x77, 0xc2),
+    (0x7f, 0x7f, 0x7f),
+    (0xbc, 0xbd, 0x22),
+    (0x17, 0xbe, 0xcf),
+];
+
+/// The colour of nodes without a community.
+const DEFAULT_COLOUR: (u8, u8, u8) = (0xa0, 0xa0, 0xa0);
+
+/// The node size range in GraphML and GEXF; scores are scaled into it.
+const MIN_SIZE: f64 = 10.0;
+const MAX_SIZE: f64 = 50.0;
+
+/// A file format to export to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Dot,
+    GraphMl,
+    Gexf,
+}
+
+impl Format {
+    /// Picks the format from a file extension: `.dot` or `.gv`, `.graphml`, or `.gexf`.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "dot" | "gv" => Some(Format::Dot),
+            "graphml" => Some(Format::GraphMl),
+            "gexf" => Some(Format::Gexf),
+            _ => None,
+        }
+    }
+}
+
+/// A node to export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportNode {
+    pub label: String,
+    pub community: Option<usize>,
+    pub score: Option<f64>,
+}
+
+/// An edge to export, between node indexes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportEdge {
+    pub source: usize,
+    pub target: usize,
+    pub label: String,
+}
+
+/// A graph ready to be written to a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportGraph {
+    pub directed: bool,
+    pub nodes: Vec<ExportNode>,
+    pub edges: Vec<ExportEdge>,
+}
+
+impl ExportGraph {
+    /// Converts a petgraph graph, labelling nodes and edges with their `Display` text.
+    pub fn from_graph<N: Display, E: Display, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Self {
+        Self {
+            directed: graph.is_directed(),
+            nodes: graph
+                .node_weights()
+                .map(|node| ExportNode {
+                    label: node.to_string(),
+                    community: None,
+                    score: None,
+                })
+                .collect(),
+            edges: graph
+                .edge_references()
+                .map(|edge| ExportEdge {
+                    source: edge.source().index(),
+                    target: edge.target().index(),
+                    label: edge.weight().to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Sets the community of each node, in node index order. Fails unless there is
+    /// one community per node.
+    pub fn with_communities(mut self, communities: Vec<usize>) -> Result<Self, Box<dyn Error>> {
+        self.check_len("communities", communities.len())?;
+        for (node, community) in self.nodes.iter_mut().zip(communities) {
+            node.community = Some(community);
+        }
+        Ok(self)
+    }
+
+    /// Sets the score of each node, in node index order. Fails unless there is one
+    /// score per node.
+    pub fn with_scores(mut self, scores: Vec<f64>) -> Result<Self, Box<dyn Error>> {
+        self.check_len("scores", scores.len())?;
+        for (node, score) in self.nodes.iter_mut().zip(scores) {
+            node.score = Some(score);
+        }
+        Ok(self)
+    }
+
+    /// Writes the graph in the given format.
+    pub fn write<W: Write>(&self, format: Format, writer: W) -> io::Result<()> {
+        match format {
+            Format::Dot => dot::write(self, writer),
+            Format::GraphMl => graphml::write(self, writer),
+            Format::Gexf => gexf::write(self, writer),
+        }
+    }
+
+    /// Writes the graph to a file, in the format given by its extension.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let path = path.as_ref();
+        let format = Format::from_path(path).ok_or_else(|| {
+            format!(
+                "cannot tell the export format of {}; use .dot, .gv, .graphml or .gexf",
+                path.display()
+            )
+        })?;
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(format, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    // Checks that a list of node values has one value per node.
+    fn check_len(&self, values: &str, len: usize) -> Result<(), Box<dyn Error>> {
+        if len != self.nodes.len() {
+            return Err(format!(
+                "got {} {} for a graph of {} nodes",
+                len,
+                values,
+                self.nodes.len()
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    // Each node's score scaled to 0..=1 across the graph; 0.5 for nodes without
+    // a score, or when every score is the same.
+    fn relative_scores(&self) -> Vec<f64> {
+        let scores = self.nodes.iter().filter_map(|node| node.score);
+        let min = scores.clone().fold(f64::INFINITY, f64::min);
+        let max = scores.fold(f64::NEG_INFINITY, f64::max);
+        self.nodes
+            .iter()
+            .map(|node| match node.score {
+                Some(score) if max > min => (score - min) / (max - min),
+                _ => 0.5,
+            })
+            .collect()
+    }
+}
+
+// The colour of a node, from its community.
+fn node_colour(node: &ExportNode) -> (u8, u8, u8) {
+    node.community.map_or(DEFAULT_COLOUR, community_colour)
+}
+
+// Scales a relative score to a node size between `MIN_SIZE` and `MAX_SIZE`.
+fn size(relative_score: f64) -> f64 {
+    MIN_SIZE + (MAX_SIZE - MIN_SIZE) * relative_score
+}
+
+/// Returns the colour of a community, from a fixed palette for the first ten and
+/// spread around the colour wheel by the golden angle after that.
+pub fn community_colour(community: usize) -> (u8, u8, u8) {
+    if let Some(&colour) = PALETTE.get(community) {
+        return colour;
+    }
+    let hue = (community as f64 * 137.508) % 360.0;
+    hsl_to_rgb(hue, 0.65, 0.5)
+}
+
+/// Formats a colour as `#rrggbb`.
+pub fn hex_colour((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> (u8, u8, u8) {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let h = hue / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    let channel = |c: f64| ((c + m) * 255.0).round() as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+/// Escapes text for XML attributes and content.
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::graph::DiGraph;
+
+    fn sample() -> ExportGraph {
+        let mut graph = DiGraph::<&str, &str>::new();
+        let a = graph.add_node("ten_gop");
+        let b = graph.add_node("jenn <abrams>");
+        let c = graph.add_node("rightnpr");
+        graph.add_edge(a, b, "retweet");
+        graph.add_edge(b, c, "mention");
+        ExportGraph::from_graph(&graph)
+            .with_communities(vec![0, 0, 1])
+            .and_then(|export| export.with_scores(vec![1.0, 2.0, 3.0]))
+            .unwrap()
+    }
+
+    fn export(format: Format) -> String {
+        let mut output = Vec::new();
+        sample().write(format, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_one_value_per_node() {
+        let error = sample().with_scores(vec![1.0]).unwrap_err();
+        assert_eq!(error.to_string(), "got 1 scores for a graph of 3 nodes");
+        assert!(sample().with_communities(vec![0, 1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn test_dot() {
+        let dot = export(Format::Dot);
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.contains(r##"1 [label="jenn <abrams>", community=0, score=2, style=filled, fillcolor="#1f77b4", width=1]"##));
+        assert!(dot.contains(r#"0 -> 1 [label="retweet"]"#));
+    }
+
+    #[test]
+    fn test_graphml() {
+        let graphml = export(Format::GraphMl);
+        assert!(graphml.contains(r#"<graph id="G" edgedefault="directed">"#));
+        assert!(graphml.contains(r#"<data key="label">jenn &lt;abrams&gt;</data>"#));
+        assert!(graphml.contains(r#"<data key="r">255</data>"#));
+        assert!(graphml.contains(r#"<data key="size">50</data>"#));
+        assert!(graphml.contains(r#"<edge id="e1" source="n1" target="n2">"#));
+    }
+
+    #[test]
+    fn test_gexf() {
+        let gexf = export(Format::Gexf);
+        assert!(gexf.contains(r#"<node id="1" label="jenn &lt;abrams&gt;">"#));
+        assert!(gexf.contains(r#"<attvalue for="community" value="1"/>"#));
+        assert!(gexf.contains(r#"<viz:color r="31" g="119" b="180"/>"#));
+        assert!(gexf.contains(r#"<viz:size value="10"/>"#));
+        assert!(gexf.contains(r#"<edge id="0" source="0" target="1" label="retweet"/>"#));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(Format::from_path("graph.GEXF"), Some(Format::Gexf));
+        assert_eq!(Format::from_path("graph.gv"), Some(Format::Dot));
+        assert_eq!(Format::from_path("graph.csv"), None);
+        assert_ne!(community_colour(10), community_colour(11));
+    }
+}
@@ -0,0 +1,230 @@
+/* Terminal charts of a dataset: line charts, horizontal bar charts and sparklines.
+
+Line charts are drawn by `rasciigraph`, with every series on the same axes, but
+have no labels along the x axis. Bar charts draw one row per label and series,
+with the label beside the bar, and sparklines squeeze each series into a single
+line of block characters, with the labels of its lowest and highest values.
+
+Bars start at 0 and are scaled to the largest value of their series, in eighths
+of a character; negative values draw no bar.
+*/
+
+use crate::table::Dataset;
+use rasciigraph::{plot_many, Config};
+
+/// The widest a bar gets, in characters.
+pub const DEFAULT_BAR_WIDTH: usize = 40;
+
+// Partial blocks for the last character of a bar, in eighths.
+const BAR_EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+// Block characters from the lowest to the highest sparkline value.
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Draws every series as a line on the same axes, with the series names listed
+/// under the caption.
+pub fn line_chart(dataset: &Dataset, caption: &str, height: u32) -> String {
+    let series: Vec<Vec<f64>> = dataset.series.iter().map(|s| s.values.clone()).collect();
+    let names: Vec<&str> = dataset.series.iter().map(|s| s.name.as_str()).collect();
+    let chart = plot_many(
+        series,
+        Config::default()
+            .with_offset(10)
+            .with_height(height)
+            .with_caption(caption.to_string()),
+    );
+    format!("{}\nSeries: {}", chart, names.join(", "))
+}
+
+/// Draws a horizontal bar for every label and series, scaled to `width`
+/// characters for the largest value of each series, followed by the value.
+///
+/// # Example
+///
+/// ```
+/// use graph_visualise::charts::bar_chart;
+/// use graph_visualise::table::{Dataset, Series};
+///
+/// let dataset = Dataset {
+///     labels: vec!["Lisbon".to_string(), "Madrid".to_string()],
+///     series: vec![Series { name: "km".to_string(), values: vec![0.0, 500.0] }],
+/// };
+/// assert_eq!(bar_chart(&dataset, 4), "Lisbon │     0\nMadrid │████ 500");
+/// ```
+pub fn bar_chart(dataset: &Dataset, width: usize) -> String {
+    let label_width = column_width(&dataset.labels);
+    let names: Vec<String> = dataset.series.iter().map(|s| s.name.clone()).collect();
+    let name_width = if dataset.series.len() > 1 {
+        column_width(&names)
+    } else {
+        0
+    };
+    let maxima: Vec<f64> = dataset
+        .series
+        .iter()
+        .map(|s| s.values.iter().copied().fold(0.0, f64::max))
+        .collect();
+
+    let mut lines = Vec::new();
+    for (row, label) in dataset.labels.iter().enumerate() {
+        for (i, series) in dataset.series.iter().enumerate() {
+            // The label goes on the first bar of each group only.
+            let label = if i == 0 { label.as_str() } else { "" };
+            let name = if name_width > 0 {
+                format!("{:<name_width$} ", series.name)
+            } else {
+                String::new()
+            };
+            let value = series.values[row];
+            lines.push(format!(
+                "{:<label_width$} │{}{:<width$} {}",
+                label,
+                name,
+                bar(value, maxima[i], width),
+                format_value(value)
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Draws every series as a sparkline, followed by its lowest and highest values
+/// and their labels.
+pub fn sparklines(dataset: &Dataset) -> String {
+    let names: Vec<String> = dataset.series.iter().map(|s| s.name.clone()).collect();
+    let name_width = column_width(&names);
+
+    dataset
+        .series
+        .iter()
+        .map(|series| {
+            let mut line = format!(
+                "{:<name_width$}  {}",
+                series.name,
+                sparkline(&series.values)
+            );
+            let lowest = extreme(&series.values, |a, b| a < b);
+            let highest = extreme(&series.values, |a, b| a > b);
+            if let (Some(low), Some(high)) = (lowest, highest) {
+                line.push_str(&format!(
+                    "  min {} ({}), max {} ({})",
+                    format_value(series.values[low]),
+                    dataset.labels[low],
+                    format_value(series.values[high]),
+                    dataset.labels[high]
+                ));
+            }
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Draws values as a line of block characters, from `▁` for the lowest to `█` for
+/// the highest. Values that are not finite are drawn as spaces.
+pub fn sparkline(values: &[f64]) -> String {
+    let finite = values.iter().copied().filter(|v| v.is_finite());
+    let min = finite.clone().fold(f64::INFINITY, f64::min);
+    let max = finite.fold(f64::NEG_INFINITY, f64::max);
+
+    values
+        .iter()
+        .map(|&value| {
+            if !value.is_finite() {
+                ' '
+            } else if max > min {
+                let level = ((value - min) / (max - min) * 7.0).round() as usize;
+                SPARKS[level]
+            } else {
+                SPARKS[3]
+            }
+        })
+        .collect()
+}
+
+// A bar of up to `width` characters for a value, scaled to the maximum.
+fn bar(value: f64, max: f64, width: usize) -> String {
+    if max <= 0.0 || value <= 0.0 {
+        return String::new();
+    }
+    let eighths = (value / max * (width * 8) as f64).round() as usize;
+    let mut bar = "█".repeat(eighths / 8);
+    if !eighths.is_multiple_of(8) {
+        bar.push(BAR_EIGHTHS[eighths % 8]);
+    }
+    bar
+}
+
+// The index of the first value that beats every other by `better`.
+fn extreme(values: &[f64], better: fn(f64, f64) -> bool) -> Option<usize> {
+    (0..values.len())
+        .filter(|&i| values[i].is_finite())
+        .reduce(|best, i| {
+            if better(values[i], values[best]) {
+                i
+            } else {
+                best
+            }
+        })
+}
+
+// Values without a needless fractional part, and at most two decimals otherwise.
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{}", value)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+fn column_width(texts: &[String]) -> usize {
+    texts
+        .iter()
+        .map(|text| text.chars().count())
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::table::Series;
+
+    fn dataset() -> Dataset {
+        Dataset {
+            labels: vec!["Lisbon".into(), "Madrid".into(), "Paris".into()],
+            series: vec![
+                Series {
+                    name: "distance_km".into(),
+                    values: vec![0.0, 502.56, 1053.36],
+                },
+                Series {
+                    name: "population_k".into(),
+                    values: vec![545.0, 3280.0, 2100.0],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_bar_chart_labels_every_group() {
+        let chart = bar_chart(&dataset(), 8);
+        let lines: Vec<&str> = chart.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[0], "Lisbon │distance_km           0");
+        assert_eq!(lines[3], "       │population_k ████████ 3280");
+        // 502.56 / 1053.36 of 64 eighths is 31: three full blocks and seven eighths.
+        assert_eq!(lines[2], "Madrid │distance_km  ███▉     502.56");
+    }
+
+    #[test]
+    fn test_sparklines() {
+        assert_eq!(sparkline(&[0.0, 1.0, 7.0]), "▁▂█");
+        assert_eq!(sparkline(&[2.0, 2.0]), "▄▄");
+        let lines = sparklines(&dataset());
+        assert_eq!(
+            lines.lines().next().unwrap(),
+            "distance_km   ▁▄█  min 0 (Lisbon), max 1053.36 (Paris)"
+        );
+    }
+}
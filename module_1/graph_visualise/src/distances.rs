@@ -0,0 +1,334 @@
+/* Parsing `city:distance` files, with a diagnostic for every line that is skipped.
+
+Every non-blank line must hold a city name and a number separated by exactly one
+`:`. A line is skipped, with its line number and reason, when it is not valid
+UTF-8, has no separator or more than one, has no city name, has a value that is
+not a finite, non-negative number, or repeats a city already read; the first
+value of a city is kept.
+
+Parsing always reads the whole file, so every problem is found at once. In
+strict mode any problem fails the read with a report of them all; in lenient
+mode the problems are returned alongside the distances, to be shown as warnings.
+*/
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+/// How to treat lines that cannot be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Fail with every problem.
+    Strict,
+    /// Skip the lines and report the problems.
+    Lenient,
+}
+
+/// Why a line was skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// The line is not valid UTF-8.
+    Unreadable,
+    MissingSeparator,
+    /// More than one `:`, with how many were found.
+    ExtraSeparators(usize),
+    MissingCity,
+    /// The text that is not a finite, non-negative number.
+    BadNumber(String),
+    /// The city, and the line it was first read on.
+    DuplicateCity {
+        city: String,
+        first_line: usize,
+    },
+}
+
+impl Reason {
+    /// A short name for the kind of problem, for summaries.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Reason::Unreadable => "unreadable",
+            Reason::MissingSeparator => "missing separator",
+            Reason::ExtraSeparators(_) => "extra separators",
+            Reason::MissingCity => "missing city",
+            Reason::BadNumber(_) => "bad number",
+            Reason::DuplicateCity { .. } => "duplicate city",
+        }
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Reason::Unreadable => write!(f, "the line is not valid UTF-8"),
+            Reason::MissingSeparator => write!(f, "missing `:` between the city and the distance"),
+            Reason::ExtraSeparators(count) => {
+                write!(f, "expected one `:` separator, found {}", count)
+            }
+            Reason::MissingCity => write!(f, "missing city name"),
+            Reason::BadNumber(text) => write!(f, "bad number `{}`", text),
+            Reason::DuplicateCity { city, first_line } => write!(
+                f,
+                "duplicate city `{}`, first read on line {}",
+                city, first_line
+            ),
+        }
+    }
+}
+
+/// A skipped line and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// The line number, from 1.
+    pub line: usize,
+    pub reason: Reason,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+/// The distances read from a file, and the lines that were skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distances {
+    pub distances: HashMap<String, f64>,
+    /// The number of lines in the file, blank lines included.
+    pub lines: usize,
+    pub problems: Vec<Problem>,
+}
+
+impl Distances {
+    /// Summarises the read: how many cities were read, and how many lines were
+    /// skipped for each kind of problem.
+    pub fn summary(&self) -> String {
+        let cities = match self.distances.len() {
+            1 => "1 city".to_string(),
+            count => format!("{} cities", count),
+        };
+        let mut summary = format!("Read {} from {}", cities, plural(self.lines, "line"));
+        if !self.problems.is_empty() {
+            let mut kinds: BTreeMap<&str, usize> = BTreeMap::new();
+            for problem in &self.problems {
+                *kinds.entry(problem.reason.kind()).or_default() += 1;
+            }
+            let kinds: Vec<String> = kinds
+                .iter()
+                .map(|(kind, count)| format!("{} {}", kind, count))
+                .collect();
+            summary.push_str(&format!(
+                "; skipped {} ({})",
+                plural(self.problems.len(), "line"),
+                kinds.join(", ")
+            ));
+        }
+        summary
+    }
+}
+
+/// The problems that failed a strict read of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The file that was read, as shown to the user.
+    pub path: String,
+    pub problems: Vec<Problem>,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: found {}:",
+            self.path,
+            plural(self.problems.len(), "problem")
+        )?;
+        for problem in &self.problems {
+            write!(f, "\n  {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {}
+
+/// Reads a `city:distance` file. In strict mode, any problem fails the read with
+/// a [`ParseError`] listing them all.
+///
+/// # Example
+///
+/// ```no_run
+/// use graph_visualise::distances::{read_distances, Mode};
+///
+/// let read = read_distances("data/distances.txt", Mode::Lenient).unwrap();
+/// for problem in &read.problems {
+///     eprintln!("warning: {}", problem);
+/// }
+/// println!("{}", read.summary());
+/// ```
+pub fn read_distances<P: AsRef<Path>>(path: P, mode: Mode) -> Result<Distances, Box<dyn Error>> {
+    let path = path.as_ref();
+    let read = parse_distances(BufReader::new(File::open(path)?))?;
+    if mode == Mode::Strict && !read.problems.is_empty() {
+        return Err(Box::new(ParseError {
+            path: path.display().to_string(),
+            problems: read.problems,
+        }));
+    }
+    Ok(read)
+}
+
+/// Parses `city:distance` lines, collecting every problem. Blank lines are
+/// skipped without one.
+pub fn parse_distances<R: BufRead>(mut reader: R) -> io::Result<Distances> {
+    let mut distances = HashMap::new();
+    let mut first_lines: HashMap<String, usize> = HashMap::new();
+    let mut problems = Vec::new();
+    let mut bytes = Vec::new();
+    let mut line = 0;
+
+    loop {
+        bytes.clear();
+        if reader.read_until(b'\n', &mut bytes)? == 0 {
+            break;
+        }
+        line += 1;
+
+        let mut skip = |reason| problems.push(Problem { line, reason });
+        let Ok(text) = std::str::from_utf8(&bytes) else {
+            skip(Reason::Unreadable);
+            continue;
+        };
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        let parts: Vec<&str> = text.split(':').map(str::trim).collect();
+        let (city, value) = match parts.as_slice() {
+            [_] => {
+                skip(Reason::MissingSeparator);
+                continue;
+            }
+            [city, value] => (*city, *value),
+            _ => {
+                skip(Reason::ExtraSeparators(parts.len() - 1));
+                continue;
+            }
+        };
+        if city.is_empty() {
+            skip(Reason::MissingCity);
+            continue;
+        }
+        let distance = match value.parse::<f64>() {
+            Ok(distance) if distance.is_finite() && distance >= 0.0 => distance,
+            _ => {
+                skip(Reason::BadNumber(value.to_string()));
+                continue;
+            }
+        };
+        if let Some(&first_line) = first_lines.get(city) {
+            skip(Reason::DuplicateCity {
+                city: city.to_string(),
+                first_line,
+            });
+            continue;
+        }
+
+        first_lines.insert(city.to_string(), line);
+        distances.insert(city.to_string(), distance);
+    }
+
+    Ok(Distances {
+        distances,
+        lines: line,
+        problems,
+    })
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_problem_is_reported() {
+        let text = b"Lisbon:0.0\nMadrid 502.56\n\nParis:far\nNyon:1:2\n:3.0\nLisbon:9.9\n\xff:1\nBerlin:2187.27\n";
+        let read = parse_distances(&text[..]).unwrap();
+
+        assert_eq!(read.distances.len(), 2);
+        assert_eq!(read.distances["Lisbon"], 0.0);
+        assert_eq!(read.lines, 9);
+        let reasons: Vec<(usize, Reason)> = read
+            .problems
+            .iter()
+            .map(|problem| (problem.line, problem.reason.clone()))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (2, Reason::MissingSeparator),
+                (4, Reason::BadNumber("far".to_string())),
+                (5, Reason::ExtraSeparators(2)),
+                (6, Reason::MissingCity),
+                (
+                    7,
+                    Reason::DuplicateCity {
+                        city: "Lisbon".to_string(),
+                        first_line: 1
+                    }
+                ),
+                (8, Reason::Unreadable),
+            ]
+        );
+        assert_eq!(
+            read.summary(),
+            "Read 2 cities from 9 lines; skipped 6 lines (bad number 1, duplicate city 1, \
+             extra separators 1, missing city 1, missing separator 1, unreadable 1)"
+        );
+    }
+
+    #[test]
+    fn test_distances_must_be_finite_and_non_negative() {
+        let text = b"Lisbon:0.0\nParis:inf\nNyon:NaN\nMadrid:-5\nBerlin:-0.0\n";
+        let read = parse_distances(&text[..]).unwrap();
+
+        assert_eq!(read.distances.len(), 2);
+        let reasons: Vec<Reason> = read
+            .problems
+            .into_iter()
+            .map(|problem| problem.reason)
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                Reason::BadNumber("inf".to_string()),
+                Reason::BadNumber("NaN".to_string()),
+                Reason::BadNumber("-5".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_error_report() {
+        let read = parse_distances(&b"Paris:far\nNyon\n"[..]).unwrap();
+        let error = ParseError {
+            path: "distances.txt".to_string(),
+            problems: read.problems,
+        };
+        assert_eq!(
+            error.to_string(),
+            "distances.txt: found 2 problems:\n  line 1: bad number `far`\n  \
+             line 2: missing `:` between the city and the distance"
+        );
+    }
+}
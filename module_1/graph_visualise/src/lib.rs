@@ -0,0 +1,11 @@
+/* Terminal charts of labelled values.
+
+The `distances` module reads `city:distance` files, reporting every line it
+cannot read, and the `table` module reads CSV and TSV files with a header row
+and picks the column of labels and the columns of values to plot. The `charts`
+module draws the values as line charts, horizontal bar charts or sparklines.
+*/
+
+pub mod charts;
+pub mod distances;
+pub mod table;
@@ -1,32 +1,109 @@
+/* This program reads a list of cities and their distances from a source city (Lisbon)
+from a file, and visualizes the distances as an ASCII graph in the terminal. The cities are printed
+in order of increasing distance from the source city.
+
+The distances are stored in a `HashMap` which is populated from a txt file, and the
+cities are sorted based on their distances before printing and plotting.
+
+The file format should look like this:
+```text
+Lisbon:0.0
+Madrid:502.56
+Paris:1053.36
+...
+```
+
+CSV and TSV files with a header row can be plotted too. `--label` picks the column of labels
+(the first column by default) and `--values` the columns to plot (every numeric column by
+default), so several series can be drawn at once. Besides line charts, `--chart` draws
+horizontal bar charts or sparklines, which show the label of each value:
+
+cargo run -- data/cities.csv --values distance_km,population_k --chart bar
+cargo run -- data/distances.txt --chart sparkline
+
+Lines of a `city:distance` file that cannot be read (a bad number, a repeated city, a missing
+or extra `:`) are skipped with a warning giving their line number and reason, and a summary of
+how many were skipped. With `--strict`, any such line fails the program with a report of them all.
+*/
+
+use clap::{Parser, ValueEnum};
+use graph_visualise::charts::{bar_chart, line_chart, sparklines, DEFAULT_BAR_WIDTH};
+use graph_visualise::distances::{read_distances, Mode};
+use graph_visualise::table::{delimiter_for, load_table, Dataset, Series};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-extern crate rasciigraph;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// A `city:distance` file, or a CSV or TSV file with a header row
+    #[arg(default_value = "data/distances.txt")]
+    file: PathBuf,
+
+    /// The column of labels in a CSV or TSV file; the first column by default
+    #[arg(short, long)]
+    label: Option<String>,
+
+    /// The columns to plot from a CSV or TSV file, separated by commas; every numeric column by
+    /// default
+    #[arg(short, long, value_delimiter = ',')]
+    values: Vec<String>,
+
+    /// The field delimiter of a table, as a single character or `tab`; by default it follows
+    /// the extension, `.csv` or `.tsv`
+    #[arg(short, long, value_parser = parse_delimiter)]
+    delimiter: Option<u8>,
+
+    /// The kind of chart to draw
+    #[arg(short, long, value_enum, default_value_t = Chart::Line)]
+    chart: Chart,
+
+    /// Sort a table's rows by its first plotted column, in increasing order
+    #[arg(short, long)]
+    sort: bool,
+
+    /// Fail on any unreadable line of a `city:distance` file, listing every problem, instead of
+    /// skipping it with a warning
+    #[arg(long)]
+    strict: bool,
+
+    /// The width of the longest bar in a bar chart, in characters
+    #[arg(short, long, default_value_t = DEFAULT_BAR_WIDTH)]
+    width: usize,
+}
 
-use rasciigraph::{plot, Config};
+/// The kinds of chart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Chart {
+    Line,
+    Bar,
+    Sparkline,
+}
 
-/// This program reads a list of cities and their distances from a source city (Lisbon)
-/// from a file, and visualizes the distances as an ASCII graph in the terminal. The cities are printed
-/// in order of increasing distance from the source city.
-///
-/// The distances are stored in a `HashMap` which is populated from a txt file, and the
-/// cities are sorted based on their distances before printing and plotting.
-///
-/// The file format should look like this:
-/// ```text
-/// Lisbon:0.0
-/// Madrid:502.56
-/// Paris:1053.36
-/// ...
-/// ```
+/// Parses a delimiter given as a single character or `tab`.
+fn parse_delimiter(text: &str) -> Result<u8, String> {
+    match text {
+        "tab" | "\\t" => Ok(b'\t'),
+        _ if text.len() == 1 => Ok(text.as_bytes()[0]),
+        _ => Err(format!(
+            "invalid delimiter `{}`; expected a single character or `tab`",
+            text
+        )),
+    }
+}
 
 /// Reads a file and returns a hashmap of cities and distances from the source city.
 ///
+/// In lenient mode, every line that cannot be read is skipped with a warning on stderr,
+/// followed by a summary of how many lines were skipped and why. In strict mode, any such
+/// line fails the read with a report of every problem and its line number.
+///
 /// # Arguments
 ///
 /// * `file_path` - A string slice that holds the file path of the distances file.
-///    "root/data/distances.txt"
+///   "root/data/distances.txt"
+/// * `mode` - Whether to fail on lines that cannot be read, or skip them.
 ///
 /// # Returns
 ///
@@ -46,7 +123,7 @@ use rasciigraph::{plot, Config};
 /// let mut writer = BufWriter::new(File::create(file_path).unwrap());
 /// writer.write_all(sample_data.as_bytes()).unwrap();
 ///
-/// let distances = read_file(file_path).unwrap();
+/// let distances = read_file(file_path, Mode::Strict).unwrap();
 /// assert_eq!(distances["Lisbon"], 0.0);
 /// assert_eq!(distances["Madrid"], 502.56);
 /// assert_eq!(distances["Paris"], 1053.36);
@@ -54,61 +131,92 @@ use rasciigraph::{plot, Config};
 /// std::fs::remove_file(file_path).unwrap(); // Cleanup after test
 /// ```
 ///
-fn read_file(file_path: &str) -> io::Result<HashMap<String, f64>> {
-    let path = Path::new(file_path);
-    let file = File::open(path)?;
-    let lines = io::BufReader::new(file).lines();
-    let mut distances = HashMap::new();
-
-    for line in lines {
-        if let Ok(entry) = line {
-            let parts: Vec<&str> = entry.split(':').collect();
-            if parts.len() == 2 {
-                let city = parts[0].trim().to_string();
-                if let Ok(value) = parts[1].trim().parse::<f64>() {
-                    distances.insert(city, value);
-                }
-            }
+fn read_file(file_path: &str, mode: Mode) -> Result<HashMap<String, f64>, Box<dyn Error>> {
+    let read = read_distances(file_path, mode)?;
+    if !read.problems.is_empty() {
+        for problem in &read.problems {
+            eprintln!("warning: {}: {}", file_path, problem);
         }
+        eprintln!("{}", read.summary());
     }
-    Ok(distances)
+    Ok(read.distances)
 }
 
-fn main() -> io::Result<()> {
-    // Reading data from file
-    let distances_map = read_file("data/distances.txt")?;
+fn main() {
+    // Print errors with Display, so that multi-line reports stay readable
+    if let Err(error) = run(Args::parse()) {
+        eprintln!("Error: {}", error);
+        std::process::exit(1);
+    }
+}
 
-    // Extracting cities and distances and sorting by increasing distance
-    let mut city_distance_pairs: Vec<(&String, &f64)> = distances_map.iter().collect();
-    city_distance_pairs.sort_by(|a, b| a.1.partial_cmp(b.1).unwrap());
-
-    let cities: Vec<String> = city_distance_pairs
-        .iter()
-        .map(|&(city, _)| city.clone())
-        .collect();
-    let distances: Vec<f64> = city_distance_pairs
-        .iter()
-        .map(|&(_, &distance)| distance)
-        .collect();
-
-    // Print city path in order of increasing distance
-    println!("{}", cities.join(" > "));
-
-    // Plot ASCII graph
-    println!(
-        "{}",
-        plot(
-            distances.iter().copied().collect(),
-            Config::default()
-                .with_offset(10)
-                .with_height(10)
-                .with_caption("Travelled distances (km)".to_string())
-        )
-    );
+/// Reads the file given on the command line and draws the chosen chart.
+fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let delimiter = args.delimiter.or_else(|| delimiter_for(&args.file));
+    let (dataset, caption) = match delimiter {
+        Some(delimiter) => {
+            if args.strict {
+                return Err("--strict applies to city:distance files".into());
+            }
+            let table = load_table(&args.file, delimiter)?;
+            let mut dataset = table.select(args.label.as_deref(), &args.values)?;
+            if args.sort {
+                dataset.sort_by_series(0);
+            }
+            let caption = args.file.display().to_string();
+            (dataset, caption)
+        }
+        None => {
+            if args.label.is_some() || !args.values.is_empty() {
+                return Err("--label and --values need a CSV or TSV file".into());
+            }
+            let mode = if args.strict {
+                Mode::Strict
+            } else {
+                Mode::Lenient
+            };
+            let dataset = distance_dataset(&args.file, mode)?;
+
+            // Print city path in order of increasing distance
+            println!("{}", dataset.labels.join(" > "));
+            (dataset, "Travelled distances (km)".to_string())
+        }
+    };
+
+    let chart = match args.chart {
+        Chart::Line => line_chart(&dataset, &caption, 10),
+        Chart::Bar => bar_chart(&dataset, args.width),
+        Chart::Sparkline => sparklines(&dataset),
+    };
+    println!("{}", chart);
 
     Ok(())
 }
 
+/// Reads a `city:distance` file into a dataset of one series, sorted by increasing distance.
+fn distance_dataset(path: &Path, mode: Mode) -> Result<Dataset, Box<dyn Error>> {
+    let path = path.to_str().ok_or("the file path is not valid UTF-8")?;
+    let distances_map = read_file(path, mode)?;
+
+    // Extracting cities and distances and sorting by increasing distance
+    let mut city_distance_pairs: Vec<(&String, &f64)> = distances_map.iter().collect();
+    city_distance_pairs.sort_by(|a, b| a.1.total_cmp(b.1));
+
+    Ok(Dataset {
+        labels: city_distance_pairs
+            .iter()
+            .map(|&(city, _)| city.clone())
+            .collect(),
+        series: vec![Series {
+            name: "distance_km".to_string(),
+            values: city_distance_pairs
+                .iter()
+                .map(|&(_, &distance)| distance)
+                .collect(),
+        }],
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,7 +230,7 @@ mod tests {
 
         std::fs::write(file_path, sample_data).unwrap();
 
-        let distances = read_file(file_path).unwrap();
+        let distances = read_file(file_path, Mode::Strict).unwrap();
 
         // Validate contents
         assert_eq!(distances["Lisbon"], 0.0);
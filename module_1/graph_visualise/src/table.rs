@@ -0,0 +1,251 @@
+/* Reading delimited tables and choosing the columns to plot.
+
+A table is a CSV or TSV file with a header row. One column gives the labels,
+such as city names, and one or more numeric columns give the series to plot:
+```text
+city,distance_km,population_k
+Lisbon,0.0,545
+Madrid,502.56,3280
+```
+When no value columns are chosen, every other column whose values are all
+numbers is plotted. `inf` and `NaN` are not numbers here, as they cannot be drawn.
+*/
+
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// The rows of a delimited file, as text, under its header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// A named series of values, one per label.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    pub name: String,
+    pub values: Vec<f64>,
+}
+
+/// Labels and the series to plot against them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dataset {
+    pub labels: Vec<String>,
+    pub series: Vec<Series>,
+}
+
+impl Table {
+    /// Returns the index of the column with the given header.
+    pub fn column(&self, name: &str) -> Option<usize> {
+        self.headers.iter().position(|header| header == name)
+    }
+
+    /// Picks the label column and the value columns to plot, by header. Without a
+    /// label column the first column is used, and without value columns every
+    /// other all-numeric column is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graph_visualise::table::read_table;
+    ///
+    /// let csv = "city,distance_km,country\nLisbon,0.0,Portugal\nMadrid,502.56,Spain\n";
+    /// let table = read_table(csv.as_bytes(), b',').unwrap();
+    /// let dataset = table.select(None, &[]).unwrap();
+    /// assert_eq!(dataset.labels, vec!["Lisbon", "Madrid"]);
+    /// assert_eq!(dataset.series[0].values, vec![0.0, 502.56]);
+    /// ```
+    pub fn select(&self, label: Option<&str>, values: &[String]) -> Result<Dataset, String> {
+        let label = match label {
+            Some(name) => self.require(name)?,
+            None if self.headers.is_empty() => return Err("the table has no columns".into()),
+            None => 0,
+        };
+        if self.rows.is_empty() {
+            return Err("the table has no rows to plot".into());
+        }
+        let columns = if values.is_empty() {
+            let numeric: Vec<usize> = (0..self.headers.len())
+                .filter(|&column| column != label && self.is_numeric(column))
+                .collect();
+            if numeric.is_empty() {
+                return Err("the table has no numeric columns to plot".into());
+            }
+            numeric
+        } else {
+            values
+                .iter()
+                .map(|name| self.require(name))
+                .collect::<Result<_, _>>()?
+        };
+
+        let series = columns
+            .into_iter()
+            .map(|column| {
+                let values = self
+                    .rows
+                    .iter()
+                    .enumerate()
+                    .map(|(row, cells)| {
+                        parse_number(&cells[column]).ok_or_else(|| {
+                            format!(
+                                "row {}: `{}` in column `{}` is not a number",
+                                row + 1,
+                                cells[column],
+                                self.headers[column]
+                            )
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(Series {
+                    name: self.headers[column].clone(),
+                    values,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Dataset {
+            labels: self.rows.iter().map(|cells| cells[label].clone()).collect(),
+            series,
+        })
+    }
+
+    // The index of a column that must exist.
+    fn require(&self, name: &str) -> Result<usize, String> {
+        self.column(name).ok_or_else(|| {
+            format!(
+                "no column `{}`; the columns are {}",
+                name,
+                self.headers.join(", ")
+            )
+        })
+    }
+
+    fn is_numeric(&self, column: usize) -> bool {
+        !self.rows.is_empty()
+            && self
+                .rows
+                .iter()
+                .all(|cells| parse_number(&cells[column]).is_some())
+    }
+}
+
+// A cell's value, if it is a finite number; `inf` and `NaN` cannot be plotted.
+fn parse_number(cell: &str) -> Option<f64> {
+    cell.parse::<f64>().ok().filter(|value| value.is_finite())
+}
+
+impl Dataset {
+    /// Sorts the labels and every series by the values of one series, in increasing order.
+    pub fn sort_by_series(&mut self, series: usize) {
+        let key = &self.series[series].values;
+        let mut order: Vec<usize> = (0..self.labels.len()).collect();
+        order.sort_by(|&a, &b| key[a].total_cmp(&key[b]));
+
+        self.labels = order.iter().map(|&i| self.labels[i].clone()).collect();
+        for series in &mut self.series {
+            series.values = order.iter().map(|&i| series.values[i]).collect();
+        }
+    }
+}
+
+/// Picks the delimiter from a file extension: a tab for `.tsv` and `.tab`, a comma
+/// for `.csv`, and `None` for anything else.
+pub fn delimiter_for<P: AsRef<Path>>(path: P) -> Option<u8> {
+    let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "tsv" | "tab" => Some(b'\t'),
+        "csv" => Some(b','),
+        _ => None,
+    }
+}
+
+/// Reads a table from a file with the given delimiter.
+pub fn load_table<P: AsRef<Path>>(path: P, delimiter: u8) -> Result<Table, Box<dyn Error>> {
+    read_table(File::open(path)?, delimiter)
+}
+
+/// Reads a table with a header row. Every row must have as many fields as the header.
+pub fn read_table<R: Read>(reader: R, delimiter: u8) -> Result<Table, Box<dyn Error>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let headers = rdr.headers()?.iter().map(str::to_string).collect();
+    let mut rows = Vec::new();
+
+    for (row, record) in rdr.records().enumerate() {
+        let record = record.map_err(|e| format!("row {}: {}", row + 1, e))?;
+        rows.push(record.iter().map(str::to_string).collect());
+    }
+
+    Ok(Table { headers, rows })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_columns() {
+        let tsv = "city\tdistance_km\tpopulation_k\nMadrid\t502.56\t3280\nLisbon\t0.0\t545\n";
+        let table = read_table(tsv.as_bytes(), b'\t').unwrap();
+
+        let mut dataset = table
+            .select(Some("city"), &["population_k".to_string()])
+            .unwrap();
+        assert_eq!(dataset.series.len(), 1);
+        assert_eq!(dataset.series[0].values, vec![3280.0, 545.0]);
+
+        dataset.sort_by_series(0);
+        assert_eq!(dataset.labels, vec!["Lisbon", "Madrid"]);
+
+        let all = table.select(None, &[]).unwrap();
+        assert_eq!(all.series.len(), 2);
+
+        let error = table.select(Some("town"), &[]).unwrap_err();
+        assert!(error.contains("city, distance_km, population_k"));
+        let error = table
+            .select(Some("distance_km"), &["city".to_string()])
+            .unwrap_err();
+        assert!(error.starts_with("row 1: `Madrid`"));
+    }
+
+    #[test]
+    fn test_non_finite_cells_are_not_numbers() {
+        let csv = "city,distance_km,population_k\nMadrid,502.56,inf\nLisbon,-1.5,NaN\n";
+        let table = read_table(csv.as_bytes(), b',').unwrap();
+
+        let all = table.select(None, &[]).unwrap();
+        assert_eq!(all.series.len(), 1);
+        assert_eq!(all.series[0].name, "distance_km");
+
+        let error = table
+            .select(None, &["population_k".to_string()])
+            .unwrap_err();
+        assert_eq!(
+            error,
+            "row 1: `inf` in column `population_k` is not a number"
+        );
+    }
+
+    #[test]
+    fn test_header_only_table() {
+        let table = read_table("city,population_k\n".as_bytes(), b',').unwrap();
+        let error = table
+            .select(Some("city"), &["population_k".to_string()])
+            .unwrap_err();
+        assert_eq!(error, "the table has no rows to plot");
+        assert!(table.select(None, &[]).is_err());
+    }
+
+    #[test]
+    fn test_delimiter_from_extension() {
+        assert_eq!(delimiter_for("cities.TSV"), Some(b'\t'));
+        assert_eq!(delimiter_for("cities.csv"), Some(b','));
+        assert_eq!(delimiter_for("distances.txt"), None);
+    }
+}
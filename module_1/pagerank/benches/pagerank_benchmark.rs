@@ -0,0 +1,78 @@
+// Compares the sequential adjacency-list PageRank with the parallel CSR PageRank
+// on a random graph. Adjust the graph size and number of threads to see how it
+// impacts performance:
+//
+// cargo bench
+// or
+// cargo bench -- 1000000 10
+// or
+// RAYON_NUM_THREADS=4 cargo bench -- 1000000 10
+//
+// The arguments are the number of nodes and the average number of out-links per node.
+// Timings include only the ranking, not building the graphs.
+
+use pagerank::csr::CsrGraph;
+use pagerank::PageRank;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::Instant;
+
+fn main() {
+    // `cargo bench` passes `--bench`, so only numeric arguments are read.
+    let args: Vec<usize> = std::env::args()
+        .skip(1)
+        .filter_map(|arg| arg.parse().ok())
+        .collect();
+    let nodes = args.first().copied().unwrap_or(1_000_000);
+    let degree = args.get(1).copied().unwrap_or(10);
+
+    // Generates a reproducible random link graph.
+    let mut rng = StdRng::seed_from_u64(42);
+    let adjacency: Vec<Vec<usize>> = (0..nodes)
+        .map(|_| {
+            let links = rng.gen_range(0..=2 * degree);
+            (0..links).map(|_| rng.gen_range(0..nodes)).collect()
+        })
+        .collect();
+    let edges: usize = adjacency.iter().map(Vec::len).sum();
+
+    let start = Instant::now();
+    let csr = CsrGraph::from_adjacency(&adjacency);
+    println!("Nodes: {}, edges: {}", nodes, edges);
+    println!("CSR build duration: {:?}", start.elapsed());
+    println!("Number of threads: {}", rayon::current_num_threads());
+
+    // Runs a fixed number of iterations so both sides do the same work.
+    let pagerank = PageRank::new(0.85, 20)
+        .unwrap()
+        .with_tolerance(0.0)
+        .unwrap();
+
+    let start = Instant::now();
+    let sequential = pagerank.rank(&adjacency);
+    let sequential_duration = start.elapsed();
+    println!(
+        "Sequential duration: {:?} ({} iterations)",
+        sequential_duration, sequential.iterations
+    );
+
+    let start = Instant::now();
+    let parallel = pagerank.rank_csr(&csr);
+    let parallel_duration = start.elapsed();
+    println!(
+        "Parallel CSR duration: {:?} ({} iterations)",
+        parallel_duration, parallel.iterations
+    );
+
+    let max_difference = sequential
+        .ranks
+        .iter()
+        .zip(&parallel.ranks)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f64::max);
+    println!(
+        "Speed-up: {:.2}x, largest rank difference: {:e}",
+        sequential_duration.as_secs_f64() / parallel_duration.as_secs_f64(),
+        max_difference
+    );
+}
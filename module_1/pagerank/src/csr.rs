@@ -0,0 +1,231 @@
+/* Parallel PageRank over a compressed sparse row (CSR) graph.
+
+The nested `Vec<Vec<usize>>` adjacency list is easy to build but scatters every
+node's links across its own heap allocation. A CSR graph stores all links in one
+flat array, with an offsets array marking where each node's links start, which
+keeps memory compact and access sequential on graphs with millions of nodes.
+
+The graph is stored transposed: for each node we keep the nodes that link *to*
+it. Each iteration can then "pull" its new rank from its in-links independently
+of every other node, so rayon can compute the nodes in parallel without locks.
+*/
+
+use crate::teleport::{TeleportError, TeleportVector};
+use crate::{PageRank, PageRankResult};
+use rayon::prelude::*;
+
+/// A link graph in compressed sparse row form, indexed by target node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsrGraph {
+    /// `sources[offsets[v]..offsets[v + 1]]` are the nodes linking to node `v`.
+    offsets: Vec<usize>,
+    sources: Vec<usize>,
+    /// The number of out-links of each node.
+    out_degree: Vec<usize>,
+}
+
+impl CsrGraph {
+    /// Builds a CSR graph with `n` nodes from `(source, target)` links.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a link refers to a node index of `n` or more.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use pagerank::csr::CsrGraph;
+    ///
+    /// let graph = CsrGraph::from_edges(3, &[(0, 1), (1, 2), (2, 0), (0, 2)]);
+    /// assert_eq!(graph.node_count(), 3);
+    /// assert_eq!(graph.edge_count(), 4);
+    /// ```
+    pub fn from_edges(n: usize, edges: &[(usize, usize)]) -> Self {
+        Self::build(n, || edges.iter().copied())
+    }
+
+    /// Builds a CSR graph from an adjacency list, as taken by `PageRank::rank`.
+    pub fn from_adjacency(graph: &[Vec<usize>]) -> Self {
+        Self::build(graph.len(), || {
+            graph
+                .iter()
+                .enumerate()
+                .flat_map(|(source, targets)| targets.iter().map(move |&target| (source, target)))
+        })
+    }
+
+    // Counts the links in a first pass over them and places them in a second, so
+    // no copy of the links is needed.
+    fn build<I, F>(n: usize, edges: F) -> Self
+    where
+        I: Iterator<Item = (usize, usize)>,
+        F: Fn() -> I,
+    {
+        let mut out_degree = vec![0; n];
+        let mut offsets = vec![0; n + 1];
+        for (source, target) in edges() {
+            assert!(
+                source < n && target < n,
+                "link ({}, {}) is out of range for {} nodes",
+                source,
+                target,
+                n
+            );
+            out_degree[source] += 1;
+            offsets[target + 1] += 1;
+        }
+
+        // Turns the in-degree counts into running offsets.
+        for i in 0..n {
+            offsets[i + 1] += offsets[i];
+        }
+
+        // Places each source in its target's slot.
+        let mut next = offsets.clone();
+        let mut sources = vec![0; offsets[n]];
+        for (source, target) in edges() {
+            sources[next[target]] = source;
+            next[target] += 1;
+        }
+
+        Self {
+            offsets,
+            sources,
+            out_degree,
+        }
+    }
+
+    /// Returns the number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.out_degree.len()
+    }
+
+    /// Returns the number of links.
+    pub fn edge_count(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Returns the nodes that link to `node`.
+    pub fn in_links(&self, node: usize) -> &[usize] {
+        &self.sources[self.offsets[node]..self.offsets[node + 1]]
+    }
+}
+
+impl PageRank {
+    /// Calculates PageRank over a CSR graph, computing each iteration in parallel.
+    /// Gives the same ranks as `PageRank::rank` on the equivalent adjacency list.
+    pub fn rank_csr(&self, graph: &CsrGraph) -> PageRankResult {
+        self.iterate_csr(graph, None)
+    }
+
+    /// Calculates personalized PageRank over a CSR graph in parallel.
+    /// See `PageRank::rank_personalized`.
+    pub fn rank_csr_personalized(
+        &self,
+        graph: &CsrGraph,
+        teleport: &TeleportVector,
+    ) -> Result<PageRankResult, TeleportError> {
+        if teleport.len() != graph.node_count() {
+            return Err(TeleportError::LengthMismatch {
+                expected: graph.node_count(),
+                found: teleport.len(),
+            });
+        }
+        Ok(self.iterate_csr(graph, Some(teleport.weights())))
+    }
+
+    // The pull-based power iteration. Every step is a parallel pass over the nodes,
+    // and all buffers are allocated once up front.
+    fn iterate_csr(&self, graph: &CsrGraph, teleport: Option<&[f64]>) -> PageRankResult {
+        let n = graph.node_count();
+        if n == 0 {
+            return PageRankResult {
+                ranks: Vec::new(),
+                iterations: 0,
+                residual: 0.0,
+                converged: true,
+            };
+        }
+
+        let mut ranks = vec![1.0 / (n as f64); n];
+        let mut new_ranks = vec![0.0; n];
+        // The share of its rank each node passes along every out-link.
+        let mut contributions = vec![0.0; n];
+        let mut iterations = 0;
+        let mut residual = f64::INFINITY;
+
+        while iterations < self.iterations && residual > self.tolerance {
+            // Splits each node's rank over its out-links and collects the dangling mass.
+            let dangling: f64 = contributions
+                .par_iter_mut()
+                .zip(ranks.par_iter().zip(&graph.out_degree))
+                .map(|(contribution, (&rank, &degree))| {
+                    if degree == 0 {
+                        *contribution = 0.0;
+                        rank
+                    } else {
+                        *contribution = rank / degree as f64;
+                        0.0
+                    }
+                })
+                .sum();
+
+            // Pulls each node's new rank from its in-links and measures the L1 change.
+            let jump = self.damping * dangling + (1.0 - self.damping);
+            residual = new_ranks
+                .par_iter_mut()
+                .enumerate()
+                .map(|(node, new_rank)| {
+                    let linked: f64 = graph
+                        .in_links(node)
+                        .iter()
+                        .map(|&source| contributions[source])
+                        .sum();
+                    let teleport = match teleport {
+                        Some(weights) => weights[node],
+                        None => 1.0 / n as f64,
+                    };
+                    *new_rank = linked * self.damping + jump * teleport;
+                    (*new_rank - ranks[node]).abs()
+                })
+                .sum();
+
+            std::mem::swap(&mut ranks, &mut new_ranks);
+            iterations += 1;
+        }
+
+        PageRankResult {
+            ranks,
+            iterations,
+            residual,
+            converged: residual <= self.tolerance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_links() {
+        let graph = CsrGraph::from_adjacency(&[vec![1, 2], vec![2], vec![0]]);
+
+        assert_eq!(graph.in_links(0), &[2]);
+        assert_eq!(graph.in_links(1), &[0]);
+        assert_eq!(graph.in_links(2), &[0, 1]);
+    }
+
+    #[test]
+    fn test_rank_csr_matches_rank() {
+        let adjacency = vec![vec![1, 2], vec![0], vec![0, 3], vec![], vec![0, 1]];
+        let pagerank = PageRank::new(0.85, 100).unwrap();
+        let expected = pagerank.rank(&adjacency);
+        let result = pagerank.rank_csr(&CsrGraph::from_adjacency(&adjacency));
+
+        assert_eq!(result.iterations, expected.iterations);
+        for (a, b) in result.ranks.iter().zip(&expected.ranks) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+/* HITS (Hyperlink-Induced Topic Search), also known as hubs and authorities.
+
+Where PageRank gives each page a single score, HITS gives it two: a good
+authority is a page linked to by many good hubs, and a good hub is a page that
+links to many good authorities. The two scores are refined in turn until they
+stop changing.
+
+HITS takes the same adjacency list as `PageRank::rank`. Scores are normalized
+to sum to 1 after every iteration, so they can be read alongside PageRank.
+*/
+
+use crate::DEFAULT_TOLERANCE;
+
+// The Hits struct holds the maximum number of iterations to run the algorithm
+// and the tolerance at which it stops early.
+pub struct Hits {
+    iterations: usize,
+    tolerance: f64,
+}
+
+/// The outcome of a HITS run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HitsResult {
+    /// The hub score of each node, summing to 1.
+    pub hubs: Vec<f64>,
+    /// The authority score of each node, summing to 1.
+    pub authorities: Vec<f64>,
+    /// The number of iterations that were run.
+    pub iterations: usize,
+    /// The L1 change in hub and authority scores during the last iteration.
+    pub residual: f64,
+    /// Whether the residual dropped below the tolerance before the iteration limit.
+    pub converged: bool,
+}
+
+impl Hits {
+    // The new function creates a new instance of the Hits struct.
+    // `iterations` is the maximum number of iterations to run.
+    pub fn new(iterations: usize) -> Self {
+        Self {
+            iterations,
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+
+    // Sets the L1 tolerance below which the iteration stops early.
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    // The scores function calculates the hub and authority score of each node.
+    // A graph without any links has no hubs or authorities, so every score is 0.
+    pub fn scores(&self, graph: &[Vec<usize>]) -> HitsResult {
+        let n = graph.len();
+        let mut hubs = vec![1.0 / (n as f64); n];
+        let mut authorities = vec![1.0 / (n as f64); n];
+        let mut new_hubs = vec![0.0; n];
+        let mut new_authorities = vec![0.0; n];
+        let mut iterations = 0;
+        let mut residual = if n == 0 { 0.0 } else { f64::INFINITY };
+
+        while iterations < self.iterations && residual > self.tolerance {
+            // A node's authority is the sum of the hub scores of the nodes linking to it.
+            new_authorities.fill(0.0);
+            for (node, edges) in graph.iter().enumerate() {
+                for &edge in edges {
+                    new_authorities[edge] += hubs[node];
+                }
+            }
+            normalize(&mut new_authorities);
+
+            // A node's hub score is the sum of the authorities it links to.
+            for (hub, edges) in new_hubs.iter_mut().zip(graph) {
+                *hub = edges.iter().map(|&edge| new_authorities[edge]).sum();
+            }
+            normalize(&mut new_hubs);
+
+            residual = l1_distance(&hubs, &new_hubs) + l1_distance(&authorities, &new_authorities);
+            std::mem::swap(&mut hubs, &mut new_hubs);
+            std::mem::swap(&mut authorities, &mut new_authorities);
+            iterations += 1;
+        }
+
+        HitsResult {
+            hubs,
+            authorities,
+            iterations,
+            residual,
+            converged: residual <= self.tolerance,
+        }
+    }
+}
+
+// Scales the scores to sum to 1, leaving all-zero scores unchanged.
+fn normalize(scores: &mut [f64]) {
+    let sum: f64 = scores.iter().sum();
+    if sum > 0.0 {
+        for score in scores {
+            *score /= sum;
+        }
+    }
+}
+
+fn l1_distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).abs()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hubs_and_authorities() {
+        // Nodes 0 and 1 both link to 2 and 3; node 3 also links back to 0.
+        let graph = vec![vec![2, 3], vec![2, 3], vec![], vec![0]];
+        let result = Hits::new(100).scores(&graph);
+
+        assert!(result.converged);
+        assert!(result.hubs[0] > result.hubs[3]);
+        assert!(result.authorities[2] > result.authorities[0]);
+        assert_eq!(result.authorities[1], 0.0);
+        assert!((result.hubs.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!((result.authorities.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_graph_without_links() {
+        let result = Hits::new(100).scores(&[vec![], vec![]]);
+
+        assert_eq!(result.hubs, vec![0.0, 0.0]);
+        assert_eq!(result.authorities, vec![0.0, 0.0]);
+        assert!(result.converged);
+    }
+}
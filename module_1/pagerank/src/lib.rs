@@ -0,0 +1,339 @@
+/* PageRank is a link analysis algorithm used by Google
+that uses the hyperlink structure of the web to determine a quality ranking
+for each web page. It works by counting the number and quality of links to a
+page to determine a rough estimate of how important the website is.
+
+The graph is an adjacency list: each index represents a page, and the values
+in its vector are the indexes of the pages it links to. The `loader` module
+reads an edge-list file with string node labels into such a list, or into a CSR
+graph for the parallel ranking.
+
+Personalized PageRank replaces the uniform jump of the random surfer with a
+teleport vector from the `teleport` module, biasing the ranks towards chosen nodes.
+The `weighted` module ranks petgraph graphs, splitting rank by edge weight, and
+the `csr` module ranks large graphs in parallel from a compressed sparse row layout.
+The `hits` module scores the same adjacency list with hubs and authorities.
+*/
+
+pub mod csr;
+pub mod hits;
+pub mod loader;
+pub mod teleport;
+pub mod weighted;
+
+use std::error::Error;
+use std::fmt;
+use teleport::{TeleportError, TeleportVector};
+
+/// The default convergence tolerance on the L1 change in ranks between iterations.
+pub const DEFAULT_TOLERANCE: f64 = 1e-10;
+
+// The PageRank struct holds the damping factor, the maximum number of iterations to run the
+// algorithm and the tolerance at which it stops early.
+pub struct PageRank {
+    damping: f64,
+    iterations: usize,
+    tolerance: f64,
+}
+
+/// The reasons a PageRank configuration can be rejected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParameterError {
+    /// The damping factor is not between 0 and 1.
+    InvalidDamping(f64),
+    /// The tolerance is negative or NaN.
+    InvalidTolerance(f64),
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParameterError::InvalidDamping(damping) => {
+                write!(f, "damping factor must be between 0 and 1, got {}", damping)
+            }
+            ParameterError::InvalidTolerance(tolerance) => write!(
+                f,
+                "tolerance must be a non-negative number, got {}",
+                tolerance
+            ),
+        }
+    }
+}
+
+impl Error for ParameterError {}
+
+/// The outcome of a PageRank run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageRankResult {
+    /// The rank of each node, summing to 1.
+    pub ranks: Vec<f64>,
+    /// The number of iterations that were run.
+    pub iterations: usize,
+    /// The L1 change in ranks during the last iteration.
+    pub residual: f64,
+    /// Whether the residual dropped below the tolerance before the iteration limit.
+    pub converged: bool,
+}
+
+impl PageRank {
+    // The new function creates a new instance of the PageRank struct.
+    // `iterations` is the maximum number of iterations to run. The damping factor
+    // must be between 0 and 1.
+    pub fn new(damping: f64, iterations: usize) -> Result<Self, ParameterError> {
+        if !(0.0..=1.0).contains(&damping) {
+            return Err(ParameterError::InvalidDamping(damping));
+        }
+        Ok(Self {
+            damping,
+            iterations,
+            tolerance: DEFAULT_TOLERANCE,
+        })
+    }
+
+    // Sets the L1 tolerance below which the iteration stops early. It must not be
+    // negative or NaN, or the iteration could never stop early.
+    // A tolerance of zero always runs the maximum number of iterations.
+    pub fn with_tolerance(mut self, tolerance: f64) -> Result<Self, ParameterError> {
+        if tolerance.is_nan() || tolerance < 0.0 {
+            return Err(ParameterError::InvalidTolerance(tolerance));
+        }
+        self.tolerance = tolerance;
+        Ok(self)
+    }
+
+    // The rank function calculates the PageRank for each node in the graph.
+    // Nodes without out-links (dangling nodes) spread their rank uniformly over all
+    // nodes, so no rank mass is lost and the ranks always sum to 1.
+    pub fn rank(&self, graph: &[Vec<usize>]) -> PageRankResult {
+        self.iterate(graph.len(), None, |ranks, new_ranks| {
+            spread(graph, ranks, new_ranks)
+        })
+    }
+
+    // The rank_personalized function calculates personalized PageRank: the random
+    // surfer teleports, and leaves dangling nodes, according to the teleport vector
+    // instead of uniformly.
+    pub fn rank_personalized(
+        &self,
+        graph: &[Vec<usize>],
+        teleport: &TeleportVector,
+    ) -> Result<PageRankResult, TeleportError> {
+        if teleport.len() != graph.len() {
+            return Err(TeleportError::LengthMismatch {
+                expected: graph.len(),
+                found: teleport.len(),
+            });
+        }
+        Ok(
+            self.iterate(graph.len(), Some(teleport.weights()), |ranks, new_ranks| {
+                spread(graph, ranks, new_ranks)
+            }),
+        )
+    }
+
+    // Runs the power iteration over `n` nodes, teleporting uniformly when no teleport
+    // vector is given. Each iteration calls `spread` with the current ranks and a zeroed
+    // vector to add the link contributions to; it returns the rank held by dangling nodes.
+    pub(crate) fn iterate<F>(
+        &self,
+        n: usize,
+        teleport: Option<&[f64]>,
+        mut spread: F,
+    ) -> PageRankResult
+    where
+        F: FnMut(&[f64], &mut [f64]) -> f64,
+    {
+        if n == 0 {
+            return PageRankResult {
+                ranks: Vec::new(),
+                iterations: 0,
+                residual: 0.0,
+                converged: true,
+            };
+        }
+
+        // The initial PageRank value for each node.
+        let mut ranks = vec![1.0 / (n as f64); n];
+        // A second vector to hold the updated PageRank values, reused every iteration.
+        let mut new_ranks = vec![0.0; n];
+        let mut iterations = 0;
+        let mut residual = f64::INFINITY;
+
+        // Iterates until converged or the maximum number of iterations is reached.
+        while iterations < self.iterations && residual > self.tolerance {
+            new_ranks.fill(0.0);
+            // Rank held by dangling nodes, shared out to every node below.
+            let dangling = spread(&ranks, &mut new_ranks);
+
+            // Updates the PageRank values using the damping factor and the dangling mass,
+            // both of which are shared out according to the teleport probabilities.
+            let jump = self.damping * dangling + (1.0 - self.damping);
+            match teleport {
+                Some(weights) => {
+                    for (rank, weight) in new_ranks.iter_mut().zip(weights) {
+                        *rank = *rank * self.damping + jump * weight;
+                    }
+                }
+                None => {
+                    let base = jump / (n as f64);
+                    for rank in &mut new_ranks {
+                        *rank = *rank * self.damping + base;
+                    }
+                }
+            }
+
+            // Measures the L1 change and replaces the old PageRank values with the new ones.
+            residual = ranks
+                .iter()
+                .zip(&new_ranks)
+                .map(|(old, new)| (old - new).abs())
+                .sum();
+            std::mem::swap(&mut ranks, &mut new_ranks);
+            iterations += 1;
+        }
+
+        // Returns the final PageRank values.
+        PageRankResult {
+            ranks,
+            iterations,
+            residual,
+            converged: residual <= self.tolerance,
+        }
+    }
+}
+
+// Distributes each node's rank equally over its out-links and returns the rank
+// held by dangling nodes.
+fn spread(graph: &[Vec<usize>], ranks: &[f64], new_ranks: &mut [f64]) -> f64 {
+    let mut dangling = 0.0;
+
+    // Iterates over each node and its edges in the graph.
+    for (node, edges) in graph.iter().enumerate() {
+        if edges.is_empty() {
+            dangling += ranks[node];
+            continue;
+        }
+
+        // The amount of PageRank value this node contributes to its linked nodes.
+        let contribution = ranks[node] / (edges.len() as f64);
+
+        // Distributes the PageRank value to the linked nodes.
+        for &edge in edges {
+            new_ranks[edge] += contribution;
+        }
+    }
+
+    dangling
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dangling_nodes_keep_rank_mass() {
+        // Node 2 has no out-links.
+        let graph = vec![vec![1, 2], vec![2], vec![]];
+        let result = PageRank::new(0.85, 100).unwrap().rank(&graph);
+
+        assert!(result.ranks.iter().all(|rank| rank.is_finite()));
+        assert!((result.ranks.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!(result.ranks[2] > result.ranks[1]);
+    }
+
+    #[test]
+    fn test_stops_early_when_converged() {
+        let graph = vec![vec![1], vec![0]];
+        let result = PageRank::new(0.85, 1000)
+            .unwrap()
+            .with_tolerance(1e-8)
+            .unwrap()
+            .rank(&graph);
+
+        assert!(result.converged);
+        assert!(result.iterations < 1000);
+        assert!(result.residual <= 1e-8);
+    }
+
+    #[test]
+    fn test_personalized_rank_favours_seeds() {
+        let graph = vec![vec![1], vec![2], vec![0], vec![0]];
+        let uniform = PageRank::new(0.85, 100).unwrap().rank(&graph);
+        let teleport = TeleportVector::from_seeds(4, &[3]).unwrap();
+        let personalized = PageRank::new(0.85, 100)
+            .unwrap()
+            .rank_personalized(&graph, &teleport)
+            .unwrap();
+
+        assert!(personalized.ranks[3] > uniform.ranks[3]);
+        assert!((personalized.ranks.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_personalized_rank_checks_length() {
+        let teleport = TeleportVector::new(vec![1.0]).unwrap();
+        let result = PageRank::new(0.85, 100)
+            .unwrap()
+            .rank_personalized(&[vec![1], vec![0]], &teleport);
+
+        assert_eq!(
+            result,
+            Err(TeleportError::LengthMismatch {
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_teleport_vector_validation() {
+        assert!(matches!(
+            TeleportVector::new(vec![1.5, -0.5]),
+            Err(TeleportError::InvalidWeight { index: 1, .. })
+        ));
+        assert!(matches!(
+            TeleportVector::new(vec![0.2, 0.2]),
+            Err(TeleportError::NotNormalized { .. })
+        ));
+        assert_eq!(
+            TeleportVector::normalized(vec![1.0, 3.0])
+                .unwrap()
+                .weights(),
+            &[0.25, 0.75]
+        );
+        assert_eq!(
+            TeleportVector::from_seeds(2, &[2]),
+            Err(TeleportError::SeedOutOfRange { seed: 2, nodes: 2 })
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_parameters() {
+        assert_eq!(
+            PageRank::new(1.5, 100).err(),
+            Some(ParameterError::InvalidDamping(1.5))
+        );
+        assert!(PageRank::new(f64::NAN, 100).is_err());
+
+        let pagerank = || PageRank::new(0.85, 100).unwrap();
+        assert_eq!(
+            pagerank().with_tolerance(-1e-8).err(),
+            Some(ParameterError::InvalidTolerance(-1e-8))
+        );
+        assert!(pagerank().with_tolerance(f64::NAN).is_err());
+        assert!(pagerank().with_tolerance(0.0).is_ok());
+    }
+
+    #[test]
+    fn test_reports_unconverged_runs() {
+        let graph = vec![vec![1, 2], vec![0], vec![0, 1]];
+        let result = PageRank::new(0.85, 2)
+            .unwrap()
+            .with_tolerance(0.0)
+            .unwrap()
+            .rank(&graph);
+
+        assert!(!result.converged);
+        assert_eq!(result.iterations, 2);
+    }
+}
@@ -0,0 +1,300 @@
+/* Loading link graphs from edge-list files.
+
+An edge list has one link per line, written as `source<delimiter>target`, where
+both ends are string node ids such as URLs or page names. Lines starting with `#`
+are treated as comments. Links are kept as `(source, target)` index pairs, sorted
+and without repeats, which build a CSR graph directly or an adjacency list for the
+sequential algorithms. The optional label file maps node ids to display labels
+(`id<delimiter>label`); ids that only appear in the label file are added as nodes
+without any links. A teleport file for personalized PageRank has
+`id<delimiter>weight` rows; nodes it does not mention get a weight of zero.
+
+Example edge list (CSV):
+```text
+# source,target
+ESPN,NFL
+ESPN,NBA
+NFL,ESPN
+```
+*/
+
+use crate::csr::CsrGraph;
+use crate::teleport::TeleportVector;
+use csv::ReaderBuilder;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// A link graph with string node ids mapped to the indexes used by `PageRank::rank`.
+#[derive(Debug, Default)]
+pub struct LinkGraph {
+    /// Node ids, in index order.
+    pub ids: Vec<String>,
+    /// Display labels, in index order. Defaults to the node id.
+    pub labels: Vec<String>,
+    /// Links as `(source, target)` indexes into `ids`, sorted and without repeats.
+    pub links: Vec<(usize, usize)>,
+    index: HashMap<String, usize>,
+}
+
+impl LinkGraph {
+    /// Creates an empty link graph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of nodes in the graph.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Returns `true` if the graph has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Returns the index of the node with the given id, if it exists.
+    pub fn index_of(&self, id: &str) -> Option<usize> {
+        self.index.get(id).copied()
+    }
+
+    /// Returns the index of the node with the given id, adding the node if it is new.
+    pub fn add_node(&mut self, id: &str) -> usize {
+        if let Some(&index) = self.index.get(id) {
+            return index;
+        }
+        let index = self.ids.len();
+        self.ids.push(id.to_string());
+        self.labels.push(id.to_string());
+        self.index.insert(id.to_string(), index);
+        index
+    }
+
+    /// Adds a link from `source` to `target`, adding either node if it is new.
+    /// Repeated links between the same pair of nodes are only counted once.
+    ///
+    /// Each link is inserted in order, so large graphs are better read with
+    /// [`LinkGraph::from_reader`], which sorts all of its links once.
+    pub fn add_edge(&mut self, source: &str, target: &str) {
+        let link = (self.add_node(source), self.add_node(target));
+        if let Err(position) = self.links.binary_search(&link) {
+            self.links.insert(position, link);
+        }
+    }
+
+    /// Returns the outgoing links of each node, as taken by `PageRank::rank`.
+    pub fn adjacency(&self) -> Vec<Vec<usize>> {
+        let mut adjacency = vec![Vec::new(); self.len()];
+        for &(source, target) in &self.links {
+            adjacency[source].push(target);
+        }
+        adjacency
+    }
+
+    /// Builds a CSR graph of the links for `PageRank::rank_csr`.
+    pub fn to_csr(&self) -> CsrGraph {
+        CsrGraph::from_edges(self.len(), &self.links)
+    }
+
+    /// Reads an edge list from a CSV or TSV file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the edge-list file.
+    /// * `delimiter` - The field delimiter, usually `b','` or `b'\t'`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use pagerank::loader::LinkGraph;
+    ///
+    /// let graph = LinkGraph::from_edge_list("data/sports_links.csv", b',').unwrap();
+    /// assert_eq!(graph.len(), 5);
+    /// ```
+    pub fn from_edge_list<P: AsRef<Path>>(path: P, delimiter: u8) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(path)?;
+        Self::from_reader(file, delimiter)
+    }
+
+    /// Reads an edge list from any reader. See [`LinkGraph::from_edge_list`].
+    pub fn from_reader<R: Read>(reader: R, delimiter: u8) -> Result<Self, Box<dyn Error>> {
+        let mut graph = Self::new();
+        let mut rdr = reader_builder(delimiter).from_reader(reader);
+
+        for (line, result) in rdr.records().enumerate() {
+            let record = result?;
+            match (record.get(0), record.get(1)) {
+                (Some(source), Some(target)) if !source.is_empty() && !target.is_empty() => {
+                    let link = (graph.add_node(source), graph.add_node(target));
+                    graph.links.push(link);
+                }
+                _ => {
+                    return Err(format!(
+                        "edge record {} must have a source and a target: {:?}",
+                        line + 1,
+                        record
+                    )
+                    .into())
+                }
+            }
+        }
+
+        // Repeated links are only counted once.
+        graph.links.sort_unstable();
+        graph.links.dedup();
+        Ok(graph)
+    }
+
+    /// Reads display labels from a CSV or TSV file of `id<delimiter>label` rows.
+    /// A row with only an id adds that node without changing its label.
+    pub fn load_labels<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        delimiter: u8,
+    ) -> Result<(), Box<dyn Error>> {
+        let file = File::open(path)?;
+        self.read_labels(file, delimiter)
+    }
+
+    /// Reads display labels from any reader. See [`LinkGraph::load_labels`].
+    pub fn read_labels<R: Read>(&mut self, reader: R, delimiter: u8) -> Result<(), Box<dyn Error>> {
+        let mut rdr = reader_builder(delimiter).from_reader(reader);
+
+        for result in rdr.records() {
+            let record = result?;
+            let Some(id) = record.get(0).filter(|id| !id.is_empty()) else {
+                continue;
+            };
+            let index = self.add_node(id);
+            if let Some(label) = record.get(1).filter(|label| !label.is_empty()) {
+                self.labels[index] = label.to_string();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads a teleport vector from a CSV or TSV file of `id<delimiter>weight` rows.
+    /// The weights must be non-negative and sum to 1, and every id must be a node.
+    pub fn load_teleport<P: AsRef<Path>>(
+        &self,
+        path: P,
+        delimiter: u8,
+    ) -> Result<TeleportVector, Box<dyn Error>> {
+        let file = File::open(path)?;
+        self.read_teleport(file, delimiter)
+    }
+
+    /// Reads a teleport vector from any reader. See [`LinkGraph::load_teleport`].
+    pub fn read_teleport<R: Read>(
+        &self,
+        reader: R,
+        delimiter: u8,
+    ) -> Result<TeleportVector, Box<dyn Error>> {
+        let mut weights = vec![0.0; self.len()];
+        let mut rdr = reader_builder(delimiter).from_reader(reader);
+
+        for result in rdr.records() {
+            let record = result?;
+            let (Some(id), Some(weight)) = (record.get(0), record.get(1)) else {
+                return Err(
+                    format!("teleport record must have an id and a weight: {:?}", record).into(),
+                );
+            };
+            let index = self
+                .index_of(id)
+                .ok_or_else(|| format!("teleport node {:?} is not in the graph", id))?;
+            weights[index] = weight
+                .parse()
+                .map_err(|e| format!("invalid teleport weight {:?} for {:?}: {}", weight, id, e))?;
+        }
+
+        Ok(TeleportVector::new(weights)?)
+    }
+}
+
+/// Picks the delimiter from the file extension: tab for `.tsv`, comma otherwise.
+pub fn delimiter_for<P: AsRef<Path>>(path: P) -> u8 {
+    match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("tsv") => b'\t',
+        _ => b',',
+    }
+}
+
+// Shared reader settings for edge and label files: no header row, `#` comments,
+// trimmed fields and rows of varying length.
+fn reader_builder(delimiter: u8) -> ReaderBuilder {
+    let mut builder = ReaderBuilder::new();
+    builder
+        .has_headers(false)
+        .delimiter(delimiter)
+        .comment(Some(b'#'))
+        .flexible(true)
+        .trim(csv::Trim::All);
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_reader_maps_labels_to_indexes() {
+        let data = "# source,target\nESPN,NFL\nESPN,NBA\nNFL,ESPN\nESPN,NFL\n";
+        let graph = LinkGraph::from_reader(data.as_bytes(), b',').unwrap();
+
+        assert_eq!(graph.ids, vec!["ESPN", "NFL", "NBA"]);
+        assert_eq!(graph.links, vec![(0, 1), (0, 2), (1, 0)]);
+        assert_eq!(graph.adjacency(), vec![vec![1, 2], vec![0], vec![]]);
+        assert_eq!(graph.to_csr().in_links(0), &[1]);
+        assert_eq!(graph.index_of("NBA"), Some(2));
+    }
+
+    #[test]
+    fn test_read_labels_adds_isolated_nodes() {
+        let mut graph = LinkGraph::from_reader("a\tb\n".as_bytes(), b'\t').unwrap();
+        graph
+            .read_labels("a\tPage A\nc\n".as_bytes(), b'\t')
+            .unwrap();
+
+        assert_eq!(graph.labels, vec!["Page A", "b", "c"]);
+        assert!(graph.adjacency()[2].is_empty());
+    }
+
+    #[test]
+    fn test_add_edge_matches_from_reader() {
+        let mut graph = LinkGraph::new();
+        for (source, target) in [("b", "a"), ("a", "c"), ("a", "b"), ("b", "a")] {
+            graph.add_edge(source, target);
+        }
+        let read = LinkGraph::from_reader("b,a\na,c\na,b\nb,a\n".as_bytes(), b',').unwrap();
+
+        assert_eq!(graph.links, vec![(0, 1), (1, 0), (1, 2)]);
+        assert_eq!(graph.links, read.links);
+    }
+
+    #[test]
+    fn test_from_reader_rejects_single_column_rows() {
+        assert!(LinkGraph::from_reader("a,b\nc\n".as_bytes(), b',').is_err());
+    }
+
+    #[test]
+    fn test_read_teleport() {
+        let graph = LinkGraph::from_reader("a,b\nb,c\n".as_bytes(), b',').unwrap();
+        let teleport = graph
+            .read_teleport("c,0.75\na,0.25\n".as_bytes(), b',')
+            .unwrap();
+
+        assert_eq!(teleport.weights(), &[0.25, 0.0, 0.75]);
+        assert!(graph.read_teleport("d,1.0\n".as_bytes(), b',').is_err());
+        assert!(graph.read_teleport("a,0.5\n".as_bytes(), b',').is_err());
+    }
+
+    #[test]
+    fn test_delimiter_for() {
+        assert_eq!(delimiter_for("links.tsv"), b'\t');
+        assert_eq!(delimiter_for("links.csv"), b',');
+    }
+}
@@ -7,92 +7,340 @@ Here we implement a simple example with a graph of major sports league
 websites and their links. We add a dampening factor to ensure each page has
 a minimum probability of being visited. This is a common technique used in PageRank.
 The higher the rank, the higher website will appear in searches.
+
+Any other link graph can be ranked by passing an edge-list file.
+
+Usage:
+
+cargo run
+or
+cargo run -- data/sports_links.csv --labels data/sports_labels.csv --top 3
+or
+cargo run -- links.tsv --format csv --output ranks.csv
+
+Personalized PageRank, biased towards seed nodes or a teleport file of `id,weight` rows:
+
+cargo run -- --seeds NBA,UFC
+or
+cargo run -- links.csv --teleport teleport.csv
+
+Hub and authority scores (HITS) can be printed next to each PageRank with `--hits`:
+
+cargo run -- --hits
+
+Large graphs can be ranked in parallel with `--parallel`; compare the two
+implementations with `cargo bench`.
 */
 
+use clap::{Parser, ValueEnum};
+use pagerank::hits::{Hits, HitsResult};
+use pagerank::loader::{delimiter_for, LinkGraph};
+use pagerank::teleport::TeleportVector;
+use pagerank::{PageRank, DEFAULT_TOLERANCE};
+use serde::Serialize;
+use std::cell::OnceCell;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
 // Importing the fill function from the textwrap crate to wrap text at 78 characters per line.
 use textwrap::fill;
 
-// The PageRank struct holds the damping factor and the number of iterations to run the algorithm.
-struct PageRank {
+/// Rank the nodes of a link graph with PageRank
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Edge-list file (CSV or TSV) with one `source,target` link per line.
+    /// The built-in sports websites graph is ranked when omitted.
+    edges: Option<PathBuf>,
+
+    /// Optional file of `id,label` rows giving display labels to node ids
+    #[arg(short, long)]
+    labels: Option<PathBuf>,
+
+    /// Field delimiter; defaults to tab for .tsv files and comma otherwise
+    #[arg(short, long)]
+    delimiter: Option<char>,
+
+    /// Only report the N highest ranked nodes
+    #[arg(short = 'n', long)]
+    top: Option<usize>,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "text")]
+    format: OutputFormat,
+
+    /// Write the ranking to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Personalize the ranking towards these comma-separated node ids
+    #[arg(short, long, value_delimiter = ',', conflicts_with = "teleport")]
+    seeds: Vec<String>,
+
+    /// Personalize the ranking with a file of `id,weight` rows summing to 1
+    #[arg(short, long)]
+    teleport: Option<PathBuf>,
+
+    /// The damping factor
+    #[arg(long, default_value = "0.85")]
     damping: f64,
+
+    /// The maximum number of iterations to run
+    #[arg(long, default_value = "100")]
     iterations: usize,
+
+    /// Rank in parallel over a compressed sparse row copy of the graph
+    #[arg(short, long)]
+    parallel: bool,
+
+    /// Also compute HITS hub and authority scores and print them next to the PageRank
+    #[arg(long)]
+    hits: bool,
+
+    /// Stop early once the L1 change in scores drops below this value
+    #[arg(long, default_value_t = DEFAULT_TOLERANCE)]
+    tolerance: f64,
 }
 
-impl PageRank {
-    // The new function creates a new instance of the PageRank struct.
-    fn new(damping: f64, iterations: usize) -> Self {
-        Self {
-            damping,
-            iterations,
-        }
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Csv,
+    Json,
+}
+
+// One row of the ranking output.
+#[derive(Debug, Serialize)]
+struct RankedNode<'a> {
+    position: usize,
+    id: &'a str,
+    label: &'a str,
+    rank: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hub: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    authority: Option<f64>,
+}
+
+// Builds the example graph of links between sports websites.
+fn sports_graph() -> LinkGraph {
+    let mut graph = LinkGraph::new();
+    graph.add_edge("ESPN", "NFL"); // ESPN links to NFL, NBA
+    graph.add_edge("ESPN", "NBA");
+    graph.add_edge("NFL", "ESPN"); // NFL links to ESPN
+    graph.add_edge("NBA", "ESPN"); // NBA links to ESPN, UFC
+    graph.add_edge("NBA", "UFC");
+    graph.add_edge("UFC", "ESPN"); // UFC links to ESPN
+    graph.add_edge("MLB", "ESPN"); // MLB links to ESPN, NFL
+    graph.add_edge("MLB", "NFL");
+    graph
+}
+
+// Loads the graph named on the command line, or the sports graph if none is given.
+fn load_graph(args: &Args) -> Result<LinkGraph, Box<dyn Error>> {
+    let delimiter = |path: &PathBuf| match args.delimiter {
+        Some(c) => u8::try_from(c).map_err(|_| format!("delimiter {:?} is not ASCII", c)),
+        None => Ok(delimiter_for(path)),
+    };
+
+    let mut graph = match &args.edges {
+        Some(path) => LinkGraph::from_edge_list(path, delimiter(path)?)?,
+        None => sports_graph(),
+    };
+    if let Some(path) = &args.labels {
+        graph.load_labels(path, delimiter(path)?)?;
     }
+    Ok(graph)
+}
 
-    // The rank function calculates and returns the PageRank for each node in the graph.
-    fn rank(&self, graph: &Vec<Vec<usize>>) -> Vec<f64> {
-        // The number of nodes in the graph.
-        let n = graph.len();
+// Builds the teleport vector from --seeds or --teleport, if either was given.
+fn load_teleport(args: &Args, graph: &LinkGraph) -> Result<Option<TeleportVector>, Box<dyn Error>> {
+    if let Some(path) = &args.teleport {
+        let delimiter = match args.delimiter {
+            Some(c) => u8::try_from(c).map_err(|_| format!("delimiter {:?} is not ASCII", c))?,
+            None => delimiter_for(path),
+        };
+        return Ok(Some(graph.load_teleport(path, delimiter)?));
+    }
+    if args.seeds.is_empty() {
+        return Ok(None);
+    }
 
-        // The initial PageRank value for each node.
-        let mut ranks = vec![1.0 / (n as f64); n];
+    let seeds = args
+        .seeds
+        .iter()
+        .map(|id| {
+            graph
+                .index_of(id)
+                .ok_or_else(|| format!("seed node {:?} is not in the graph", id))
+        })
+        .collect::<Result<Vec<usize>, _>>()?;
+    Ok(Some(TeleportVector::from_seeds(graph.len(), &seeds)?))
+}
 
-        // Iterates the specified number of times.
-        for _ in 0..self.iterations {
-            // A new vector to hold the updated PageRank values.
-            let mut new_ranks = vec![0.0; n];
+// Sorts nodes by descending rank and keeps the top N, with their HITS scores if computed.
+fn top_nodes<'a>(
+    graph: &'a LinkGraph,
+    ranks: &[f64],
+    hits: Option<&HitsResult>,
+    top: Option<usize>,
+) -> Vec<RankedNode<'a>> {
+    let mut order: Vec<usize> = (0..ranks.len()).collect();
+    order.sort_by(|&a, &b| ranks[b].total_cmp(&ranks[a]));
+    order.truncate(top.unwrap_or(order.len()));
 
-            // Iterates over each node and its edges in the graph.
-            for (node, edges) in graph.iter().enumerate() {
-                // The amount of PageRank value this node contributes to its linked nodes.
-                let contribution = ranks[node] / (edges.len() as f64);
+    order
+        .into_iter()
+        .enumerate()
+        .map(|(i, node)| RankedNode {
+            position: i + 1,
+            id: &graph.ids[node],
+            label: &graph.labels[node],
+            rank: ranks[node],
+            hub: hits.map(|hits| hits.hubs[node]),
+            authority: hits.map(|hits| hits.authorities[node]),
+        })
+        .collect()
+}
 
-                // Distributes the PageRank value to the linked nodes.
-                for &edge in edges {
-                    new_ranks[edge] += contribution;
+// Writes the ranking in the chosen format.
+fn write_ranking<W: Write>(
+    out: W,
+    nodes: &[RankedNode],
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        OutputFormat::Text => {
+            let mut out = out;
+            let width = label_width(nodes);
+            for node in nodes {
+                match (node.hub, node.authority) {
+                    (Some(hub), Some(authority)) => writeln!(
+                        out,
+                        "{:<width$}  PageRank {:.6}  Hub {:.6}  Authority {:.6}",
+                        node.label,
+                        node.rank,
+                        hub,
+                        authority,
+                        width = width
+                    )?,
+                    _ => writeln!(out, "The PageRank of {} is {}", node.label, node.rank)?,
                 }
             }
-
-            // Updates the PageRank values using the damping factor.
-            for rank in &mut new_ranks {
-                *rank = *rank * self.damping + (1.0 - self.damping) / (n as f64);
+            out.flush()?;
+        }
+        OutputFormat::Csv => {
+            let mut wtr = csv::Writer::from_writer(out);
+            for node in nodes {
+                wtr.serialize(node)?;
             }
-
-            // Replaces the old PageRank values with the new ones.
-            ranks = new_ranks;
+            wtr.flush()?;
+        }
+        OutputFormat::Json => {
+            let mut out = out;
+            serde_json::to_writer_pretty(&mut out, nodes)?;
+            writeln!(out)?;
+            out.flush()?;
         }
-
-        // Returns the final PageRank values.
-        ranks
     }
+    Ok(())
+}
+
+// The width of the longest label, to line up the columns of the HITS table.
+fn label_width(nodes: &[RankedNode]) -> usize {
+    nodes
+        .iter()
+        .map(|node| node.label.chars().count())
+        .max()
+        .unwrap_or(0)
 }
 
-fn main() {
-    // The graph represents links between sports websites. Each index represents a website,
-    // and the values in the vectors are the indexes of the websites they link to.
-    let graph = vec![
-        vec![1, 2], // ESPN links to NFL, NBA
-        vec![0],    // NFL links to ESPN
-        vec![0, 3], // NBA links to ESPN, UFC
-        vec![0],    // UFC links to ESPN
-        vec![0, 1], // MLB links to ESPN, NFL
-    ];
+// Reports how an iteration ended, on stderr so CSV/JSON on stdout stays clean.
+fn report_convergence(
+    algorithm: &str,
+    converged: bool,
+    iterations: usize,
+    residual: f64,
+    tolerance: f64,
+) {
+    let status = if converged {
+        "converged"
+    } else {
+        "stopped without converging"
+    };
+    eprintln!(
+        "{} {} after {} iterations (residual {:e}, tolerance {:e})",
+        algorithm, status, iterations, residual, tolerance
+    );
+}
 
-    // The names corresponding to the indexes of the websites.
-    let names = vec!["ESPN", "NFL", "NBA", "UFC", "MLB"];
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
 
-    // Initializes the PageRank struct.
-    let pagerank = PageRank::new(0.85, 100);
+    // Initializes the PageRank struct and loads the graph.
+    let pagerank = PageRank::new(args.damping, args.iterations)
+        .and_then(|pagerank| pagerank.with_tolerance(args.tolerance))
+        .map_err(|e| e.to_string())?;
+    let graph = load_graph(&args)?;
 
     // Calculates the PageRank values.
-    let ranks = pagerank.rank(&graph);
+    let teleport = load_teleport(&args, &graph)?;
+    // The adjacency list is only built if a sequential algorithm needs it.
+    let adjacency = OnceCell::new();
+    let result = if args.parallel {
+        let csr = graph.to_csr();
+        match &teleport {
+            Some(teleport) => pagerank.rank_csr_personalized(&csr, teleport)?,
+            None => pagerank.rank_csr(&csr),
+        }
+    } else {
+        let adjacency = adjacency.get_or_init(|| graph.adjacency());
+        match &teleport {
+            Some(teleport) => pagerank.rank_personalized(adjacency, teleport)?,
+            None => pagerank.rank(adjacency),
+        }
+    };
+    report_convergence(
+        "PageRank",
+        result.converged,
+        result.iterations,
+        result.residual,
+        args.tolerance,
+    );
+
+    // Calculates the hub and authority scores over the same links.
+    let hits = args.hits.then(|| {
+        let hits = Hits::new(args.iterations)
+            .with_tolerance(args.tolerance)
+            .scores(adjacency.get_or_init(|| graph.adjacency()));
+        report_convergence(
+            "HITS",
+            hits.converged,
+            hits.iterations,
+            hits.residual,
+            args.tolerance,
+        );
+        hits
+    });
+    let nodes = top_nodes(&graph, &result.ranks, hits.as_ref(), args.top);
 
-    // Prints the PageRank values.
-    for (i, rank) in ranks.iter().enumerate() {
-        println!("The PageRank of {} is {}", names[i], rank);
+    // Prints or writes the PageRank values.
+    match &args.output {
+        Some(path) => {
+            write_ranking(BufWriter::new(File::create(path)?), &nodes, args.format)?;
+            println!("Wrote {} ranked nodes to {}", nodes.len(), path.display());
+        }
+        None => write_ranking(io::stdout().lock(), &nodes, args.format)?,
     }
 
-    // Explanation of how PageRank works.
-    let explanation = "PageRank is a link analysis algorithm used by Google that uses the hyperlink structure of the web to determine a quality ranking for each web page. It works by counting the number and quality of links to a page to determine a rough estimate of how important the website is.";
+    if let (OutputFormat::Text, None) = (args.format, &args.output) {
+        // Explanation of how PageRank works.
+        let explanation = "PageRank is a link analysis algorithm used by Google that uses the hyperlink structure of the web to determine a quality ranking for each web page. It works by counting the number and quality of links to a page to determine a rough estimate of how important the website is.";
+
+        // Prints the explanation wrapped at 78 characters per line.
+        println!("{}", fill(explanation, 78));
+    }
 
-    // Prints the explanation wrapped at 78 characters per line.
-    println!("{}", fill(explanation, 78));
+    Ok(())
 }
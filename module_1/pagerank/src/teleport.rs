@@ -0,0 +1,140 @@
+/* Teleport (personalization) vectors for personalized PageRank.
+
+Plain PageRank's random surfer jumps to any page with equal probability. A
+teleport vector gives the probability of jumping to each page instead, which
+biases the ranking towards the pages it favours. A vector that puts all of its
+mass on a few seed pages ranks pages by how close they are to those seeds.
+*/
+
+use std::error::Error;
+use std::fmt;
+
+/// How far the sum of a teleport vector may be from 1.
+const SUM_TOLERANCE: f64 = 1e-9;
+
+/// A validated teleport vector: non-negative, finite probabilities that sum to 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TeleportVector {
+    weights: Vec<f64>,
+}
+
+/// The reasons a teleport vector can be rejected.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TeleportError {
+    /// The vector has no entries.
+    Empty,
+    /// An entry is negative, NaN or infinite.
+    InvalidWeight { index: usize, weight: f64 },
+    /// The entries do not sum to 1.
+    NotNormalized { sum: f64 },
+    /// No seed nodes were given.
+    NoSeeds,
+    /// A seed node is not in the graph.
+    SeedOutOfRange { seed: usize, nodes: usize },
+    /// The vector and the graph have a different number of nodes.
+    LengthMismatch { expected: usize, found: usize },
+}
+
+impl fmt::Display for TeleportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TeleportError::Empty => write!(f, "teleport vector is empty"),
+            TeleportError::InvalidWeight { index, weight } => write!(
+                f,
+                "teleport weight {} at node {} must be a non-negative number",
+                weight, index
+            ),
+            TeleportError::NotNormalized { sum } => {
+                write!(f, "teleport weights must sum to 1, got {}", sum)
+            }
+            TeleportError::NoSeeds => write!(f, "at least one seed node is required"),
+            TeleportError::SeedOutOfRange { seed, nodes } => write!(
+                f,
+                "seed node {} is out of range for a graph with {} nodes",
+                seed, nodes
+            ),
+            TeleportError::LengthMismatch { expected, found } => write!(
+                f,
+                "teleport vector has {} entries but the graph has {} nodes",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl Error for TeleportError {}
+
+impl TeleportVector {
+    /// Creates a teleport vector from probabilities that already sum to 1.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use pagerank::teleport::TeleportVector;
+    ///
+    /// assert!(TeleportVector::new(vec![0.5, 0.5, 0.0]).is_ok());
+    /// assert!(TeleportVector::new(vec![0.5, 0.6]).is_err());
+    /// ```
+    pub fn new(weights: Vec<f64>) -> Result<Self, TeleportError> {
+        let sum = check_weights(&weights)?;
+        if (sum - 1.0).abs() > SUM_TOLERANCE {
+            return Err(TeleportError::NotNormalized { sum });
+        }
+        Ok(Self { weights })
+    }
+
+    /// Creates a teleport vector from non-negative weights, scaling them to sum to 1.
+    pub fn normalized(mut weights: Vec<f64>) -> Result<Self, TeleportError> {
+        let sum = check_weights(&weights)?;
+        if sum == 0.0 {
+            return Err(TeleportError::NotNormalized { sum });
+        }
+        for weight in &mut weights {
+            *weight /= sum;
+        }
+        Ok(Self { weights })
+    }
+
+    /// Creates a teleport vector that jumps to each of the seed nodes with equal probability.
+    pub fn from_seeds(nodes: usize, seeds: &[usize]) -> Result<Self, TeleportError> {
+        if seeds.is_empty() {
+            return Err(TeleportError::NoSeeds);
+        }
+        let mut weights = vec![0.0; nodes];
+        for &seed in seeds {
+            if seed >= nodes {
+                return Err(TeleportError::SeedOutOfRange { seed, nodes });
+            }
+            weights[seed] = 1.0;
+        }
+        Self::normalized(weights)
+    }
+
+    /// Returns the teleport probability of each node.
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// Returns the number of nodes the vector covers.
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Always `false`: a validated teleport vector has at least one entry.
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+}
+
+// Checks every weight is finite and non-negative and returns their sum.
+fn check_weights(weights: &[f64]) -> Result<f64, TeleportError> {
+    if weights.is_empty() {
+        return Err(TeleportError::Empty);
+    }
+    for (index, &weight) in weights.iter().enumerate() {
+        if !weight.is_finite() || weight < 0.0 {
+            return Err(TeleportError::InvalidWeight { index, weight });
+        }
+    }
+    Ok(weights.iter().sum())
+}
@@ -0,0 +1,148 @@
+/* Weighted PageRank over petgraph graphs.
+
+Instead of splitting a node's rank equally between its links, weighted PageRank
+splits it in proportion to the edge weights, so a page linked twice as strongly
+receives twice the share. The graph can be any petgraph graph (`Graph`, `DiGraph`,
+`UnGraph`, `StableGraph`, ...): directed graphs follow outgoing edges, while
+undirected graphs follow every edge in both directions.
+
+Like `petgraph::algo::dijkstra`, the edge weight is read with a closure, so graphs
+whose edge weights are not numbers can still be ranked, e.g. `|_| 1.0`.
+*/
+
+use crate::{PageRank, PageRankResult};
+use petgraph::visit::{EdgeRef, IntoEdges, IntoNodeIdentifiers, NodeIndexable};
+
+impl PageRank {
+    /// Calculates weighted PageRank over a petgraph graph.
+    ///
+    /// Edges with a weight that is not a positive, finite number carry no rank; a node
+    /// with no such edges is treated as dangling. The ranks are indexed by
+    /// `NodeIndexable::to_index`, which is `NodeIndex::index()` for `Graph`.
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - A reference to any petgraph graph.
+    /// * `edge_weight` - A closure returning the weight of an edge.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use pagerank::PageRank;
+    /// use petgraph::graph::UnGraph;
+    ///
+    /// let graph = UnGraph::<&str, f32>::from_edges([(0, 1, 2.0), (1, 2, 1.0)]);
+    /// let result = PageRank::new(0.85, 100).unwrap().rank_graph(&graph, |e| *e.weight() as f64);
+    /// assert!(result.ranks[1] > result.ranks[0]);
+    /// ```
+    pub fn rank_graph<G, F>(&self, graph: G, mut edge_weight: F) -> PageRankResult
+    where
+        G: IntoEdges + IntoNodeIdentifiers + NodeIndexable,
+        F: FnMut(G::EdgeRef) -> f64,
+    {
+        let n = graph.node_bound();
+
+        // The outgoing links of each node with their share of its rank.
+        let mut links: Vec<Vec<(usize, f64)>> = vec![Vec::new(); n];
+        // Graphs such as `StableGraph` can leave unused indexes behind after removals.
+        let mut present = vec![false; n];
+        for node in graph.node_identifiers() {
+            present[graph.to_index(node)] = true;
+            let out = &mut links[graph.to_index(node)];
+            for edge in graph.edges(node) {
+                let weight = edge_weight(edge);
+                if weight.is_finite() && weight > 0.0 {
+                    out.push((graph.to_index(edge.target()), weight));
+                }
+            }
+
+            let total: f64 = out.iter().map(|&(_, weight)| weight).sum();
+            for (_, weight) in out.iter_mut() {
+                *weight /= total;
+            }
+        }
+
+        // Teleport only to indexes that hold a node, so unused ones end with a rank of 0.
+        let count = present.iter().filter(|&&p| p).count();
+        let teleport: Option<Vec<f64>> = (count < n).then(|| {
+            present
+                .iter()
+                .map(|&p| if p { 1.0 / count as f64 } else { 0.0 })
+                .collect()
+        });
+
+        self.iterate(n, teleport.as_deref(), |ranks, new_ranks| {
+            let mut dangling = 0.0;
+            for (node, out) in links.iter().enumerate() {
+                if out.is_empty() {
+                    dangling += ranks[node];
+                }
+                for &(target, share) in out {
+                    new_ranks[target] += ranks[node] * share;
+                }
+            }
+            dangling
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::PageRank;
+    use petgraph::graph::{DiGraph, NodeIndex, UnGraph};
+    use petgraph::stable_graph::StableDiGraph;
+
+    #[test]
+    fn test_unit_weights_match_unweighted_rank() {
+        let adjacency = vec![vec![1, 2], vec![0], vec![0, 3], vec![0], vec![0, 1]];
+        let mut graph = DiGraph::<&str, &str>::new();
+        let nodes: Vec<_> = (0..adjacency.len())
+            .map(|_| graph.add_node("page"))
+            .collect();
+        for (from, targets) in adjacency.iter().enumerate() {
+            for &to in targets {
+                graph.add_edge(nodes[from], nodes[to], "links");
+            }
+        }
+
+        let pagerank = PageRank::new(0.85, 100).unwrap();
+        let expected = pagerank.rank(&adjacency);
+        let result = pagerank.rank_graph(&graph, |_| 1.0);
+
+        for (a, b) in result.ranks.iter().zip(&expected.ranks) {
+            assert!((a - b).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_heavier_edges_receive_more_rank() {
+        // Node 0 links to 1 three times as strongly as to 2, in both directions.
+        let graph = UnGraph::<(), f32>::from_edges([(0, 1, 3.0), (0, 2, 1.0)]);
+        let result = PageRank::new(0.85, 100)
+            .unwrap()
+            .rank_graph(&graph, |e| *e.weight() as f64);
+
+        assert!(result.ranks[1] > result.ranks[2]);
+        assert!((result.ranks.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_removed_nodes_get_no_rank() {
+        let mut graph = StableDiGraph::<(), f64>::from_edges([
+            (0, 1, 1.0),
+            (1, 2, 1.0),
+            (2, 3, 1.0),
+            (3, 0, 1.0),
+            (0, 2, 1.0),
+        ]);
+        graph.remove_node(NodeIndex::new(1));
+
+        let result = PageRank::new(0.85, 100)
+            .unwrap()
+            .rank_graph(&graph, |e| *e.weight());
+
+        assert_eq!(result.ranks.len(), 4);
+        assert_eq!(result.ranks[1], 0.0);
+        assert!((result.ranks.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+}
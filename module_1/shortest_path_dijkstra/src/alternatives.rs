@@ -0,0 +1,214 @@
+/* Alternative routes with Yen's K shortest loopless paths algorithm.
+
+Yen's algorithm starts from the shortest route and finds each next-best route by
+"deviating" from the routes found so far: for every location on the previous route,
+it blocks the roads already taken from that point and finds the shortest way on to
+the destination from there. The best of these candidates becomes the next route.
+No route visits a location twice.
+
+Routes can be constrained by locations to avoid, locations that must be visited
+and a maximum distance. Routes are produced shortest first, so the search stops as
+soon as one is longer than the maximum distance.
+*/
+
+use crate::{RoadNetwork, Route};
+use petgraph::algo::astar;
+use petgraph::prelude::*;
+use petgraph::visit::EdgeFiltered;
+use std::collections::HashSet;
+
+/// The most routes Yen's algorithm examines while looking for ones that satisfy the
+/// constraints, so an impossible `via` constraint cannot enumerate every path of a
+/// large network.
+pub const MAX_EXAMINED_ROUTES: usize = 10_000;
+
+/// Constraints that every alternative route must satisfy.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RouteConstraints {
+    /// Locations the route must not pass through.
+    pub avoid: Vec<NodeIndex>,
+    /// Locations the route must pass through, in any order.
+    pub via: Vec<NodeIndex>,
+    /// The longest acceptable route, in km.
+    pub max_distance_km: Option<f64>,
+}
+
+impl RouteConstraints {
+    /// Returns `true` if no constraint is set.
+    pub fn is_empty(&self) -> bool {
+        self.avoid.is_empty() && self.via.is_empty() && self.max_distance_km.is_none()
+    }
+}
+
+impl RoadNetwork {
+    /// Finds up to `k` shortest loopless routes from `start` to `end` that satisfy the
+    /// constraints, shortest first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shortest_path_dijkstra::alternatives::RouteConstraints;
+    /// use shortest_path_dijkstra::RoadNetwork;
+    ///
+    /// let network = RoadNetwork::lisbon();
+    /// let start = network.node("Belem Tower").unwrap();
+    /// let end = network.node("Lisbon Cathedral").unwrap();
+    /// let routes = network.k_shortest_routes(start, end, 3, &RouteConstraints::default());
+    /// let distances: Vec<f64> = routes.iter().map(|r| r.distance_km).collect();
+    /// assert_eq!(distances, vec![8.5, 9.0, 9.5]);
+    /// ```
+    pub fn k_shortest_routes(
+        &self,
+        start: NodeIndex,
+        end: NodeIndex,
+        k: usize,
+        constraints: &RouteConstraints,
+    ) -> Vec<Route> {
+        let avoid: HashSet<NodeIndex> = constraints.avoid.iter().copied().collect();
+        let mut accepted = Vec::new();
+        if k == 0 || avoid.contains(&start) || avoid.contains(&end) {
+            return accepted;
+        }
+
+        // The routes found so far, shortest first, and the candidates for the next one.
+        let mut found: Vec<Route> = Vec::new();
+        let mut candidates: Vec<Route> = Vec::new();
+        let Some(shortest) = self.restricted_route(start, end, &avoid, &HashSet::new()) else {
+            return accepted;
+        };
+        let mut next = Some(shortest);
+
+        while let Some(route) = next.take() {
+            if constraints
+                .max_distance_km
+                .is_some_and(|max| route.distance_km > max)
+            {
+                break;
+            }
+            if constraints.via.iter().all(|via| route.nodes.contains(via)) {
+                accepted.push(route.clone());
+                if accepted.len() == k {
+                    break;
+                }
+            }
+            found.push(route);
+            if found.len() == MAX_EXAMINED_ROUTES {
+                break;
+            }
+
+            // Deviates from the last route at each of its locations in turn.
+            let previous = &found[found.len() - 1];
+            for i in 0..previous.nodes.len() - 1 {
+                let spur = previous.nodes[i];
+                let root = &previous.nodes[..=i];
+
+                // Blocks the next road of every found route sharing this root, so the
+                // spur must leave by a road not taken before.
+                let blocked_roads: HashSet<(NodeIndex, NodeIndex)> = found
+                    .iter()
+                    .filter(|r| r.nodes.len() > i + 1 && &r.nodes[..=i] == root)
+                    .flat_map(|r| [(r.nodes[i], r.nodes[i + 1]), (r.nodes[i + 1], r.nodes[i])])
+                    .collect();
+                // Blocks the root's other locations, so the route cannot loop back.
+                let mut blocked = avoid.clone();
+                blocked.extend(&root[..i]);
+
+                if let Some(spur_route) = self.restricted_route(spur, end, &blocked, &blocked_roads)
+                {
+                    let mut nodes = root[..i].to_vec();
+                    nodes.extend(spur_route.nodes);
+                    if !found.iter().chain(&candidates).any(|r| r.nodes == nodes) {
+                        candidates.push(self.route(nodes));
+                    }
+                }
+            }
+
+            // The shortest candidate, preferring fewer legs on a tie, is the next route.
+            next = candidates
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| {
+                    a.distance_km
+                        .total_cmp(&b.distance_km)
+                        .then(a.nodes.len().cmp(&b.nodes.len()))
+                })
+                .map(|(index, _)| index)
+                .map(|index| candidates.swap_remove(index));
+        }
+
+        accepted
+    }
+
+    // Finds the shortest route without passing through the blocked locations or
+    // taking the blocked roads, given as (from, to) pairs.
+    fn restricted_route(
+        &self,
+        start: NodeIndex,
+        end: NodeIndex,
+        blocked: &HashSet<NodeIndex>,
+        blocked_roads: &HashSet<(NodeIndex, NodeIndex)>,
+    ) -> Option<Route> {
+        let graph = EdgeFiltered::from_fn(&self.graph, |e| {
+            !blocked.contains(&e.source())
+                && !blocked.contains(&e.target())
+                && !blocked_roads.contains(&(e.source(), e.target()))
+        });
+        let (_, nodes) = astar(&graph, start, |n| n == end, |e| *e.weight(), |_| 0.0)?;
+        Some(self.route(nodes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(network: &RoadNetwork, route: &Route) -> Vec<String> {
+        route
+            .nodes
+            .iter()
+            .map(|&n| network.name(n).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_routes_are_loopless_and_ordered() {
+        let network = RoadNetwork::lisbon();
+        let start = network.node("Belem Tower").unwrap();
+        let end = network.node("Lisbon Cathedral").unwrap();
+        let routes = network.k_shortest_routes(start, end, 10, &RouteConstraints::default());
+
+        assert!(routes.len() > 3);
+        assert!(routes
+            .windows(2)
+            .all(|pair| pair[0].distance_km <= pair[1].distance_km));
+        for route in &routes {
+            let unique: HashSet<_> = route.nodes.iter().collect();
+            assert_eq!(unique.len(), route.nodes.len());
+        }
+    }
+
+    #[test]
+    fn test_constraints() {
+        let network = RoadNetwork::lisbon();
+        let start = network.node("Belem Tower").unwrap();
+        let end = network.node("Lisbon Cathedral").unwrap();
+        let constraints = RouteConstraints {
+            avoid: vec![network.node("Jerónimos Monastery").unwrap()],
+            via: vec![network.node("LX Factory").unwrap()],
+            max_distance_km: Some(10.0),
+        };
+        let routes = network.k_shortest_routes(start, end, 5, &constraints);
+
+        assert_eq!(routes.len(), 1);
+        assert_eq!(
+            names(&network, &routes[0]),
+            vec![
+                "Belem Tower",
+                "LX Factory",
+                "Commerce Square",
+                "Lisbon Cathedral"
+            ]
+        );
+        assert_eq!(routes[0].distance_km, 9.5);
+    }
+}
@@ -0,0 +1,175 @@
+/* Batch routing: finds the shortest route for every origin/destination pair in a
+CSV file and writes one result row per pair.
+
+The input file has a header row and one pair per line:
+```text
+from,to
+Belem Tower,Lisbon Cathedral
+LX Factory,Commerce Square
+```
+
+The output CSV has the columns `from,to,status,distance_km,path`, where `status`
+is `ok`, `unknown_location` or `unreachable`. Failed pairs have an empty
+distance and path, so one bad pair does not stop the rest of the batch.
+*/
+
+use crate::{RoadNetwork, RouteError};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::io::{Read, Write};
+
+/// One origin/destination pair from the input file.
+#[derive(Debug, Deserialize)]
+struct PairRecord {
+    from: String,
+    to: String,
+}
+
+/// The outcome of routing one pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteStatus {
+    Ok,
+    UnknownLocation,
+    Unreachable,
+}
+
+/// One row of the output file.
+#[derive(Debug, Serialize)]
+struct ResultRecord {
+    from: String,
+    to: String,
+    status: RouteStatus,
+    distance_km: Option<f64>,
+    path: String,
+}
+
+/// Counts of how each pair in a batch was resolved.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BatchSummary {
+    pub routed: usize,
+    pub unknown_location: usize,
+    pub unreachable: usize,
+}
+
+impl BatchSummary {
+    /// Returns the most serious outcome in the batch: an unknown location, then an
+    /// unreachable pair, then success.
+    pub fn worst_status(&self) -> RouteStatus {
+        if self.unknown_location > 0 {
+            RouteStatus::UnknownLocation
+        } else if self.unreachable > 0 {
+            RouteStatus::Unreachable
+        } else {
+            RouteStatus::Ok
+        }
+    }
+}
+
+/// Routes every pair read from `input` and writes the results as CSV to `output`.
+///
+/// # Example
+///
+/// ```
+/// use shortest_path_dijkstra::batch::run_batch;
+/// use shortest_path_dijkstra::RoadNetwork;
+///
+/// let pairs = "from,to\nBelem Tower,Lisbon Cathedral\nBelem Tower,Porto\n";
+/// let mut output = Vec::new();
+/// let summary = run_batch(&RoadNetwork::lisbon(), pairs.as_bytes(), &mut output).unwrap();
+/// assert_eq!(summary.routed, 1);
+/// assert_eq!(summary.unknown_location, 1);
+/// ```
+pub fn run_batch<R: Read, W: Write>(
+    network: &RoadNetwork,
+    input: R,
+    output: W,
+) -> Result<BatchSummary, Box<dyn Error>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input);
+    let mut wtr = csv::Writer::from_writer(output);
+    let mut summary = BatchSummary::default();
+
+    for result in rdr.deserialize() {
+        let pair: PairRecord = result?;
+        let (status, distance_km, path) = match network.route_between(&pair.from, &pair.to) {
+            Ok(route) => {
+                summary.routed += 1;
+                (
+                    RouteStatus::Ok,
+                    Some(route.distance_km),
+                    network.path_names(&route),
+                )
+            }
+            Err(RouteError::UnknownLocation(_)) => {
+                summary.unknown_location += 1;
+                (RouteStatus::UnknownLocation, None, String::new())
+            }
+            Err(RouteError::Unreachable { .. }) => {
+                summary.unreachable += 1;
+                (RouteStatus::Unreachable, None, String::new())
+            }
+        };
+
+        wtr.serialize(ResultRecord {
+            from: pair.from,
+            to: pair.to,
+            status,
+            distance_km,
+            path,
+        })?;
+    }
+
+    wtr.flush()?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two separate roads, so A and C cannot reach each other.
+    fn islands() -> RoadNetwork {
+        let mut network = RoadNetwork::new();
+        network.add_road("A", "B", 2.5);
+        network.add_road("C", "D", 1.0);
+        network
+    }
+
+    fn batch(pairs: &str) -> (BatchSummary, String) {
+        let mut output = Vec::new();
+        let summary = run_batch(&islands(), pairs.as_bytes(), &mut output).unwrap();
+        (summary, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_csv_output() {
+        let (summary, csv) = batch("from,to\nA,B\n");
+        assert_eq!(csv, "from,to,status,distance_km,path\nA,B,ok,2.5,A > B\n");
+        assert_eq!(summary.worst_status(), RouteStatus::Ok);
+    }
+
+    #[test]
+    fn test_unreachable_pair() {
+        let (summary, csv) = batch("from,to\nA,C\nA,B\n");
+        assert_eq!(csv.lines().nth(1), Some("A,C,unreachable,,"));
+        assert_eq!(
+            summary,
+            BatchSummary {
+                routed: 1,
+                unknown_location: 0,
+                unreachable: 1
+            }
+        );
+        assert_eq!(summary.worst_status(), RouteStatus::Unreachable);
+    }
+
+    #[test]
+    fn test_unknown_location_outranks_unreachable() {
+        let (summary, csv) = batch("from,to\nA,C\nA,Porto\n");
+        assert_eq!(csv.lines().nth(2), Some("A,Porto,unknown_location,,"));
+        assert_eq!((summary.unknown_location, summary.unreachable), (1, 1));
+        assert_eq!(summary.worst_status(), RouteStatus::UnknownLocation);
+    }
+}
@@ -0,0 +1,48 @@
+/* Geographic coordinates and great-circle distances.
+
+The haversine formula gives the distance between two points on a sphere from
+their latitudes and longitudes. No road between two places can be shorter than
+this straight-line distance, which makes it a suitable A* estimate for road
+networks whose distances are measured along the roads.
+*/
+
+/// The mean radius of the Earth in km.
+pub const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// A latitude and longitude in decimal degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Coordinates {
+    /// Creates coordinates from a latitude and longitude in decimal degrees.
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Self {
+            latitude,
+            longitude,
+        }
+    }
+
+    /// Returns the great-circle distance to another point in km.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shortest_path_dijkstra::geo::Coordinates;
+    ///
+    /// let lisbon = Coordinates::new(38.7223, -9.1393);
+    /// let madrid = Coordinates::new(40.4168, -3.7038);
+    /// assert!((lisbon.haversine_km(&madrid) - 502.6).abs() < 1.0);
+    /// ```
+    pub fn haversine_km(&self, other: &Coordinates) -> f64 {
+        let lat1 = self.latitude.to_radians();
+        let lat2 = other.latitude.to_radians();
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude - self.longitude).to_radians();
+
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+}
@@ -0,0 +1,511 @@
+/* A road network of named locations, loaded from a CSV of distances, and the
+routes between them.
+
+The CSV file has a header row and one road per line:
+```text
+from,to,distance_km
+Belem Tower,Jerónimos Monastery,1.5
+Belem Tower,LX Factory,3.5
+```
+Roads can be travelled in both directions.
+
+Locations can also carry coordinates, read from a second CSV file:
+```text
+name,latitude,longitude
+Belem Tower,38.6916,-9.2160
+```
+With coordinates, routes can be found with A* search, which uses the straight-line
+(haversine) distance to the destination to expand fewer locations than Dijkstra.
+
+The `batch` module routes many origin/destination pairs read from a file, and the
+`alternatives` module finds the K shortest routes subject to constraints. The
+`matrix` module computes the distance between every pair of locations, and the
+`tour` module plans the shortest round trip through a set of stops.
+*/
+
+pub mod alternatives;
+pub mod batch;
+pub mod geo;
+pub mod matrix;
+pub mod tour;
+
+use geo::Coordinates;
+use petgraph::algo::astar;
+use petgraph::graph::EdgeReference;
+use petgraph::prelude::*;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// One row of the road network CSV file.
+#[derive(Debug, Deserialize)]
+struct RoadRecord {
+    from: String,
+    to: String,
+    distance_km: f64,
+}
+
+/// One row of the locations CSV file.
+#[derive(Debug, Deserialize)]
+struct LocationRecord {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+}
+
+/// A named location, optionally with its coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub name: String,
+    pub coordinates: Option<Coordinates>,
+}
+
+/// An undirected graph of locations with road distances in km as edge weights.
+#[derive(Debug, Default)]
+pub struct RoadNetwork {
+    pub graph: UnGraph<Location, f64>,
+    node_map: HashMap<String, NodeIndex>,
+}
+
+/// The search algorithms a route can be found with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Dijkstra's algorithm, which explores outwards from the start in every direction.
+    Dijkstra,
+    /// A* search, guided towards the end by the haversine distance.
+    AStar,
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Algorithm::Dijkstra => write!(f, "Dijkstra"),
+            Algorithm::AStar => write!(f, "A*"),
+        }
+    }
+}
+
+/// A route found by a search, with the number of locations the search expanded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub route: Route,
+    pub expanded: usize,
+}
+
+/// A route through the network, with the distance of each leg.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    /// The locations visited, from start to end.
+    pub nodes: Vec<NodeIndex>,
+    /// The distance between each consecutive pair of locations.
+    pub legs: Vec<f64>,
+    /// The total distance of the route.
+    pub distance_km: f64,
+}
+
+/// The reasons a route between two named locations can fail.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RouteError {
+    /// The location is not in the network.
+    UnknownLocation(String),
+    /// There is no road connecting the two locations.
+    Unreachable { from: String, to: String },
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RouteError::UnknownLocation(name) => {
+                write!(f, "{} does not exist in the graph", name)
+            }
+            RouteError::Unreachable { from, to } => {
+                write!(f, "No route found from {} to {}", from, to)
+            }
+        }
+    }
+}
+
+impl Error for RouteError {}
+
+impl RoadNetwork {
+    /// Creates an empty road network.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the node of the named location, adding it if it is new.
+    pub fn add_location(&mut self, name: &str) -> NodeIndex {
+        if let Some(&node) = self.node_map.get(name) {
+            return node;
+        }
+        let node = self.graph.add_node(Location {
+            name: name.to_string(),
+            coordinates: None,
+        });
+        self.node_map.insert(name.to_string(), node);
+        node
+    }
+
+    /// Adds a two-way road between two locations, adding either location if it is new.
+    pub fn add_road(&mut self, from: &str, to: &str, distance_km: f64) {
+        let from = self.add_location(from);
+        let to = self.add_location(to);
+        self.graph.add_edge(from, to, distance_km);
+    }
+
+    /// The example network of landmarks in Lisbon, Portugal.
+    pub fn lisbon() -> Self {
+        let mut network = Self::new();
+        network.add_road("Belem Tower", "Jerónimos Monastery", 1.5); // The distance from Belem Tower to Jerónimos Monastery is 1.5 km
+        network.add_road("Belem Tower", "LX Factory", 3.5); // The distance from Belem Tower to LX Factory is 3.5 km
+        network.add_road("Belem Tower", "Commerce Square", 7.5); // The distance from Belem Tower to Commerce Square is 7.5 km
+        network.add_road("Jerónimos Monastery", "LX Factory", 3.0); // The distance from Jerónimos Monastery to LX Factory is 3 km
+        network.add_road("Jerónimos Monastery", "Commerce Square", 6.5); // The distance from Jerónimos Monastery to Commerce Square is 6.5 km
+        network.add_road("LX Factory", "Commerce Square", 5.0); // The distance from LX Factory to Commerce Square is 5 km
+        network.add_road("Commerce Square", "Lisbon Cathedral", 1.0); // The distance from Commerce Square to Lisbon Cathedral is 1 km
+
+        // The coordinates of each landmark, for A* search
+        network.set_coordinates("Belem Tower", Coordinates::new(38.6916, -9.2160));
+        network.set_coordinates("Jerónimos Monastery", Coordinates::new(38.6979, -9.2068));
+        network.set_coordinates("LX Factory", Coordinates::new(38.7036, -9.1789));
+        network.set_coordinates("Commerce Square", Coordinates::new(38.7075, -9.1364));
+        network.set_coordinates("Lisbon Cathedral", Coordinates::new(38.7100, -9.1334));
+        network
+    }
+
+    /// Sets the coordinates of a location, adding the location if it is new.
+    pub fn set_coordinates(&mut self, name: &str, coordinates: Coordinates) {
+        let node = self.add_location(name);
+        self.graph[node].coordinates = Some(coordinates);
+    }
+
+    /// Loads location coordinates from a CSV file of `name,latitude,longitude` rows.
+    pub fn load_coordinates<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Box<dyn Error>> {
+        let file = File::open(path)?;
+        self.read_coordinates(file)
+    }
+
+    /// Loads location coordinates from any CSV reader. See [`RoadNetwork::load_coordinates`].
+    pub fn read_coordinates<R: Read>(&mut self, reader: R) -> Result<(), Box<dyn Error>> {
+        let mut rdr = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+
+        for result in rdr.deserialize() {
+            let location: LocationRecord = result?;
+            if !(-90.0..=90.0).contains(&location.latitude)
+                || !(-180.0..=180.0).contains(&location.longitude)
+            {
+                return Err(format!(
+                    "coordinates of {} are out of range: {}, {}",
+                    location.name, location.latitude, location.longitude
+                )
+                .into());
+            }
+            self.set_coordinates(
+                &location.name,
+                Coordinates::new(location.latitude, location.longitude),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Loads a road network from a CSV file of `from,to,distance_km` rows.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use shortest_path_dijkstra::RoadNetwork;
+    ///
+    /// let network = RoadNetwork::from_csv("data/lisbon_landmarks.csv").unwrap();
+    /// assert_eq!(network.graph.node_count(), 5);
+    /// ```
+    pub fn from_csv<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(path)?;
+        Self::from_reader(file)
+    }
+
+    /// Loads a road network from any CSV reader. See [`RoadNetwork::from_csv`].
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, Box<dyn Error>> {
+        let mut network = Self::new();
+        let mut rdr = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+
+        for result in rdr.deserialize() {
+            let road: RoadRecord = result?;
+            if !road.distance_km.is_finite() || road.distance_km < 0.0 {
+                return Err(format!(
+                    "distance from {} to {} must be a non-negative number, got {}",
+                    road.from, road.to, road.distance_km
+                )
+                .into());
+            }
+            network.add_road(&road.from, &road.to, road.distance_km);
+        }
+
+        Ok(network)
+    }
+
+    /// Returns the node of the named location, if it exists.
+    pub fn node(&self, name: &str) -> Option<NodeIndex> {
+        self.node_map.get(name).copied()
+    }
+
+    /// Returns the name of a location.
+    pub fn name(&self, node: NodeIndex) -> &str {
+        &self.graph[node].name
+    }
+
+    /// Returns the names of all locations, in the order they were added.
+    pub fn locations(&self) -> impl Iterator<Item = &str> {
+        self.graph
+            .node_weights()
+            .map(|location| location.name.as_str())
+    }
+
+    /// Finds the shortest route between two locations.
+    ///
+    /// This runs `petgraph::algo::astar` with a zero estimate, which makes it behave as
+    /// Dijkstra's algorithm while also returning the path taken.
+    pub fn shortest_route(&self, start: NodeIndex, end: NodeIndex) -> Option<Route> {
+        let (_, nodes) = astar(&self.graph, start, |n| n == end, |e| *e.weight(), |_| 0.0)?;
+        Some(self.route(nodes))
+    }
+
+    /// Finds the shortest route between two locations with the chosen algorithm, and
+    /// counts the locations whose roads the search explored.
+    ///
+    /// A* estimates the remaining distance with the haversine distance, scaled by
+    /// [`RoadNetwork::heuristic_scale`] so that it never overestimates, or 0 for
+    /// locations without coordinates. It finds a route as short as Dijkstra's.
+    pub fn search(
+        &self,
+        start: NodeIndex,
+        end: NodeIndex,
+        algorithm: Algorithm,
+    ) -> Option<SearchResult> {
+        // petgraph asks for the cost of every road leaving each location it expands,
+        // so the distinct road sources are the expanded locations.
+        let mut expanded = HashSet::new();
+        let edge_cost = |e: EdgeReference<f64>| {
+            expanded.insert(e.source());
+            *e.weight()
+        };
+
+        let target = self.graph[end].coordinates;
+        let scale = self.heuristic_scale();
+        let (_, nodes) = match algorithm {
+            Algorithm::Dijkstra => astar(&self.graph, start, |n| n == end, edge_cost, |_| 0.0)?,
+            Algorithm::AStar => astar(
+                &self.graph,
+                start,
+                |n| n == end,
+                edge_cost,
+                |n| match (self.graph[n].coordinates, target) {
+                    (Some(from), Some(to)) => scale * from.haversine_km(&to),
+                    _ => 0.0,
+                },
+            )?,
+        };
+
+        Some(SearchResult {
+            route: self.route(nodes),
+            expanded: expanded.len(),
+        })
+    }
+
+    /// The factor the haversine estimate of A* is scaled by so that it is never
+    /// longer than the road distance: the smallest ratio of a road's length to the
+    /// straight line between its ends, or 1 when no road is shorter than its straight
+    /// line. A road to a location without coordinates gives 0, as the distance beyond
+    /// it cannot be bounded, and A* then expands as Dijkstra's algorithm does.
+    pub fn heuristic_scale(&self) -> f64 {
+        self.graph
+            .edge_references()
+            .map(|e| {
+                match (
+                    self.graph[e.source()].coordinates,
+                    self.graph[e.target()].coordinates,
+                ) {
+                    (Some(from), Some(to)) => {
+                        let straight = from.haversine_km(&to);
+                        if straight > 0.0 {
+                            *e.weight() / straight
+                        } else {
+                            1.0
+                        }
+                    }
+                    _ => 0.0,
+                }
+            })
+            .fold(1.0, f64::min)
+    }
+
+    /// Finds the shortest route between two named locations.
+    pub fn route_between(&self, from: &str, to: &str) -> Result<Route, RouteError> {
+        let start = self.lookup(from)?;
+        let end = self.lookup(to)?;
+        self.shortest_route(start, end)
+            .ok_or_else(|| RouteError::Unreachable {
+                from: from.to_string(),
+                to: to.to_string(),
+            })
+    }
+
+    /// Returns the node of the named location, or an error naming the unknown location.
+    pub fn lookup(&self, name: &str) -> Result<NodeIndex, RouteError> {
+        self.node(name)
+            .ok_or_else(|| RouteError::UnknownLocation(name.to_string()))
+    }
+
+    /// Joins the names of the locations on a route, e.g. `Belem Tower > LX Factory`.
+    pub fn path_names(&self, route: &Route) -> String {
+        route
+            .nodes
+            .iter()
+            .map(|&node| self.name(node))
+            .collect::<Vec<_>>()
+            .join(" > ")
+    }
+
+    // Builds a route from a list of nodes, taking the shortest road for each leg.
+    fn route(&self, nodes: Vec<NodeIndex>) -> Route {
+        let legs: Vec<f64> = nodes
+            .windows(2)
+            .map(|pair| {
+                self.graph
+                    .edges_connecting(pair[0], pair[1])
+                    .map(|e| *e.weight())
+                    .fold(f64::INFINITY, f64::min)
+            })
+            .collect();
+        let distance_km = legs.iter().sum();
+        Route {
+            nodes,
+            legs,
+            distance_km,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lisbon_roads_are_no_shorter_than_the_straight_line() {
+        let network = RoadNetwork::lisbon();
+        for edge in network.graph.edge_references() {
+            let from = network.graph[edge.source()].coordinates.unwrap();
+            let to = network.graph[edge.target()].coordinates.unwrap();
+            assert!(
+                *edge.weight() >= from.haversine_km(&to),
+                "the road from {} to {} is shorter than the straight line",
+                network.name(edge.source()),
+                network.name(edge.target())
+            );
+        }
+    }
+
+    #[test]
+    fn test_shortest_route_lists_every_leg() {
+        let network = RoadNetwork::lisbon();
+        let start = network.node("Belem Tower").unwrap();
+        let end = network.node("Lisbon Cathedral").unwrap();
+        let route = network.shortest_route(start, end).unwrap();
+
+        let names: Vec<&str> = route.nodes.iter().map(|&n| network.name(n)).collect();
+        assert_eq!(
+            names,
+            vec!["Belem Tower", "Commerce Square", "Lisbon Cathedral"]
+        );
+        assert_eq!(route.legs, vec![7.5, 1.0]);
+        assert_eq!(route.distance_km, 8.5);
+    }
+
+    #[test]
+    fn test_astar_expands_fewer_locations() {
+        // A line of towns one degree of longitude apart along the equator, with a
+        // branch going the other way from the start.
+        let mut network = RoadNetwork::new();
+        for (i, name) in ["A", "B", "C", "D", "W", "X", "Y"].iter().enumerate() {
+            let longitude = if i < 4 { i as f64 } else { 3.0 - i as f64 };
+            network.set_coordinates(name, Coordinates::new(0.0, longitude));
+        }
+        for pair in [
+            ("A", "B"),
+            ("B", "C"),
+            ("C", "D"),
+            ("A", "W"),
+            ("W", "X"),
+            ("X", "Y"),
+        ] {
+            network.add_road(pair.0, pair.1, 120.0);
+        }
+        let start = network.node("A").unwrap();
+        let end = network.node("D").unwrap();
+
+        let dijkstra = network.search(start, end, Algorithm::Dijkstra).unwrap();
+        let astar = network.search(start, end, Algorithm::AStar).unwrap();
+
+        assert_eq!(dijkstra.route, astar.route);
+        assert_eq!(astar.route.distance_km, 360.0);
+        assert!(astar.expanded < dijkstra.expanded);
+    }
+
+    #[test]
+    fn test_astar_is_exact_when_a_road_is_shorter_than_the_straight_line() {
+        // A detour through X, far to the east, is only 2 km long, much shorter than
+        // the straight line from A to X, so the unscaled estimate at X is far too high.
+        let mut network = RoadNetwork::new();
+        network.set_coordinates("A", Coordinates::new(0.0, 0.0));
+        network.set_coordinates("D", Coordinates::new(0.0, 1.0));
+        network.set_coordinates("X", Coordinates::new(0.0, 3.0));
+        network.add_road("A", "D", 200.0);
+        network.add_road("A", "X", 1.0);
+        network.add_road("X", "D", 1.0);
+        assert!(network.heuristic_scale() < 0.01);
+
+        let start = network.node("A").unwrap();
+        let end = network.node("D").unwrap();
+        let astar = network.search(start, end, Algorithm::AStar).unwrap();
+        assert_eq!(astar.route.distance_km, 2.0);
+        assert_eq!(RoadNetwork::lisbon().heuristic_scale(), 1.0);
+    }
+
+    #[test]
+    fn test_read_coordinates() {
+        let mut network = RoadNetwork::lisbon();
+        network
+            .read_coordinates("name,latitude,longitude\nPorto,41.1579,-8.6291\n".as_bytes())
+            .unwrap();
+
+        let porto = network.node("Porto").unwrap();
+        assert_eq!(
+            network.graph[porto].coordinates,
+            Some(Coordinates::new(41.1579, -8.6291))
+        );
+        assert!(network
+            .read_coordinates("name,latitude,longitude\nNowhere,91,0\n".as_bytes())
+            .is_err());
+    }
+
+    #[test]
+    fn test_from_reader() {
+        let data = "from,to,distance_km\nA, B, 2.5\nB,C,1\nC,A,10\n";
+        let network = RoadNetwork::from_reader(data.as_bytes()).unwrap();
+        let route = network
+            .shortest_route(network.node("A").unwrap(), network.node("C").unwrap())
+            .unwrap();
+
+        assert_eq!(network.locations().collect::<Vec<_>>(), vec!["A", "B", "C"]);
+        assert_eq!(route.distance_km, 3.5);
+        assert!(RoadNetwork::from_reader("from,to,distance_km\nA,B,-1\n".as_bytes()).is_err());
+    }
+}
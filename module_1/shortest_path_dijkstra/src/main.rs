@@ -3,74 +3,407 @@ The algorithm is a classic graph traversal technique for finding the shortest pa
 single source node to all other nodes in the graph.
 The example provided uses a graph representation of some landmarks in Lisbon, Portugal,
 and calculates the shortest route from Belem Tower to Lisbon Cathedral.
-The Dijkstra algorithm function returns a HashMap where the keys are the nodes and the values
-are the shortest distances from the source node to those nodes.
-Adapted to ask for user input of the source and target nodes.
+The route is found with petgraph's A* search using a zero estimate, which behaves as
+Dijkstra's algorithm but also returns the path, so each leg of the route can be printed.
+Adapted to ask for user input of the source and target nodes, and to load the graph
+from a CSV file of `from,to,distance_km` rows.
+
+Usage:
+
+cargo run
+or
+cargo run -- --graph data/lisbon_landmarks.csv
+or
+cargo run -- --from "Belem Tower" --to "Lisbon Cathedral"
+or
+cargo run -- --batch data/lisbon_pairs.csv --output routes.csv
+
+Landmarks carry coordinates, so routes can also be found with A* search. The
+`compare` algorithm runs both, reports how many locations each expanded and checks
+that they agree on the distance:
+
+cargo run -- --from "Belem Tower" --to "Lisbon Cathedral" --algorithm compare
+or
+cargo run -- --graph roads.csv --locations data/lisbon_locations.csv --algorithm astar
+
+Alternative routes (Yen's K shortest loopless paths), optionally avoiding or
+passing through locations and limited to a maximum distance:
+
+cargo run -- --from "Belem Tower" --to "Lisbon Cathedral" --alternatives 3
+or
+cargo run -- --from "Belem Tower" --to "Lisbon Cathedral" -k 5 --avoid "Jerónimos Monastery" --via "LX Factory" --max-distance 12
+
+The distance between every pair of locations, as a CSV matrix with the location
+names as headers:
+
+cargo run -- --all-pairs --output distances.csv
+
+The shortest round trip from a start location through a set of stops and back,
+solved exactly for up to 12 stops and approximately above that:
+
+cargo run -- --from "Belem Tower" --tour "LX Factory,Lisbon Cathedral,Commerce Square"
+
+Exit codes: 0 on success, 3 if a location is unknown and 4 if no route exists
+(in batch mode, if any pair failed for that reason), and 5 if Dijkstra and A*
+disagree on the distance.
 */
 
-use petgraph::algo::dijkstra;
-use petgraph::prelude::*;
-use std::collections::HashMap;
-use std::io;
-
-fn main() {
-    // Create a new undirected graph
-    let mut graph = Graph::<&str, u32, Undirected>::new_undirected();
-
-    // Add edges representing distances between landmarks
-    let belem_tower = graph.add_node("Belem Tower");
-    let monastery = graph.add_node("Jerónimos Monastery");
-    let lx_factory = graph.add_node("LX Factory");
-    let commerce_square = graph.add_node("Commerce Square");
-    let lisbon_cathedral = graph.add_node("Lisbon Cathedral");
-
-    graph.extend_with_edges([
-        (belem_tower, monastery, 1), // The distance from Belem Tower to Jerónimos Monastery is 1 km
-        (belem_tower, lx_factory, 3), // The distance from Belem Tower to LX Factory is 3 km
-        (belem_tower, commerce_square, 7), // The distance from Belem Tower to Commerce Square is 7 km
-        (monastery, lx_factory, 3), // The distance from Jerónimos Monastery to LX Factory is 3 km
-        (monastery, commerce_square, 6), // The distance from Jerónimos Monastery to Commerce Square is 6 km
-        (lx_factory, commerce_square, 5), // The distance from LX Factory to Commerce Square is 5 km
-        (commerce_square, lisbon_cathedral, 1), // The distance from Commerce Square to Lisbon Cathedral is 1 km
-    ]);
-
-    // Create a map to associate node names with their corresponding graph nodes
-    let mut node_map: HashMap<&str, NodeIndex> = HashMap::new();
-    node_map.insert("Belem Tower", belem_tower);
-    node_map.insert("Jerónimos Monastery", monastery);
-    node_map.insert("LX Factory", lx_factory);
-    node_map.insert("Commerce Square", commerce_square);
-    node_map.insert("Lisbon Cathedral", lisbon_cathedral);
-
-    // Print available nodes
-    println!("Available locations to select from:");
-    for node in node_map.keys() {
-        println!("- {}", node);
-    }
-
-    // Get user input for start and end nodes
-    let start_node = get_user_input("Enter the start location: ");
-    let end_node = get_user_input("Enter the end location: ");
-
-    // Check if the entered nodes exist in the graph
-    if let (Some(&start), Some(&end)) = (
-        node_map.get(start_node.as_str()),
-        node_map.get(end_node.as_str()),
-    ) {
-        // Calculate the shortest path from the start node to the end node using Dijkstra's algorithm
-        let distances = dijkstra(&graph, start, Some(end), |e| *e.weight());
-
-        // Check if a path was found and print the shortest distance
-        if let Some(distance) = distances.get(&end) {
+use clap::{Parser, ValueEnum};
+use shortest_path_dijkstra::alternatives::RouteConstraints;
+use shortest_path_dijkstra::batch::{run_batch, RouteStatus};
+use shortest_path_dijkstra::{Algorithm, RoadNetwork, Route, RouteError};
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Exit code when a requested location is not in the graph.
+const EXIT_UNKNOWN_LOCATION: u8 = 3;
+/// Exit code when there is no route between the requested locations.
+const EXIT_UNREACHABLE: u8 = 4;
+/// Exit code when Dijkstra and A* find routes of different lengths.
+const EXIT_MISMATCH: u8 = 5;
+
+/// Find the shortest route between two locations in a road network
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// CSV file of `from,to,distance_km` rows; the Lisbon landmarks are used when omitted
+    #[arg(short, long)]
+    graph: Option<PathBuf>,
+
+    /// CSV file of `name,latitude,longitude` rows giving coordinates for A*
+    #[arg(short, long)]
+    locations: Option<PathBuf>,
+
+    /// The search algorithm to route with; alternative and constrained routes are always
+    /// found with Yen's algorithm over Dijkstra, so only `dijkstra` is accepted with them
+    #[arg(short, long, value_enum, default_value = "dijkstra")]
+    algorithm: SearchMode,
+
+    /// The start location; prompted for when omitted
+    #[arg(short, long)]
+    from: Option<String>,
+
+    /// The end location; prompted for when omitted
+    #[arg(short, long)]
+    to: Option<String>,
+
+    /// Find up to K alternative routes, shortest first; at least 1
+    #[arg(short = 'k', long, default_value = "1", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    alternatives: usize,
+
+    /// Comma-separated locations the route must not pass through
+    #[arg(long, value_delimiter = ',')]
+    avoid: Vec<String>,
+
+    /// Comma-separated locations the route must pass through, in any order
+    #[arg(long, value_delimiter = ',')]
+    via: Vec<String>,
+
+    /// The longest acceptable route in km, above 0
+    #[arg(long, value_parser = parse_max_distance)]
+    max_distance: Option<f64>,
+
+    /// CSV file of `from,to` pairs to route in one run
+    #[arg(short, long, conflicts_with_all = ["from", "to"])]
+    batch: Option<PathBuf>,
+
+    /// Compute the distance between every pair of locations and write it as a CSV matrix
+    #[arg(long, conflicts_with_all = ["from", "to", "batch"])]
+    all_pairs: bool,
+
+    /// Comma-separated stops to visit on a round trip starting and ending at --from
+    #[arg(long, value_delimiter = ',', requires = "from", conflicts_with_all = ["to", "batch", "all_pairs"])]
+    tour: Vec<String>,
+
+    /// Write the batch results or distance matrix to this CSV file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum SearchMode {
+    Dijkstra,
+    Astar,
+    /// Run Dijkstra and A* and compare them
+    Compare,
+}
+
+/// Parses a maximum route distance, which must be a finite number of km above 0
+fn parse_max_distance(s: &str) -> Result<f64, String> {
+    let distance: f64 = s
+        .parse()
+        .map_err(|e| format!("invalid distance `{}`: {}", s, e))?;
+    if !distance.is_finite() || distance <= 0.0 {
+        return Err(format!(
+            "distance must be a finite number above 0, got {}",
+            distance
+        ));
+    }
+    Ok(distance)
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<ExitCode, Box<dyn Error>> {
+    // Load the road network, or build the Lisbon landmarks example
+    let mut network = match &args.graph {
+        Some(path) => RoadNetwork::from_csv(path)?,
+        None => RoadNetwork::lisbon(),
+    };
+    if let Some(path) = &args.locations {
+        network.load_coordinates(path)?;
+    }
+
+    if let Some(path) = &args.batch {
+        return run_batch_file(&network, path, args.output.as_ref());
+    }
+    if args.all_pairs {
+        let matrix = network.distance_matrix();
+        match &args.output {
+            Some(path) => {
+                matrix.write_csv(BufWriter::new(File::create(path)?))?;
+                eprintln!(
+                    "Wrote the {0}x{0} distance matrix to {1}",
+                    matrix.names.len(),
+                    path.display()
+                );
+            }
+            None => matrix.write_csv(io::stdout().lock())?,
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+    if args.output.is_some() {
+        return Err("--output can only be used with --batch or --all-pairs".into());
+    }
+
+    if !args.tour.is_empty() {
+        return run_tour(&network, args);
+    }
+
+    let alternatives = args.alternatives > 1
+        || !args.avoid.is_empty()
+        || !args.via.is_empty()
+        || args.max_distance.is_some();
+    if alternatives && !matches!(args.algorithm, SearchMode::Dijkstra) {
+        return Err(
+            "--algorithm cannot be combined with alternative or constrained routes, which are \
+             always found with Dijkstra"
+                .into(),
+        );
+    }
+
+    // Get the start and end nodes from the flags, or from user input
+    let interactive = args.from.is_none() || args.to.is_none();
+    if interactive {
+        // Print available nodes
+        println!("Available locations to select from:");
+        for location in network.locations() {
+            println!("- {}", location);
+        }
+    }
+    let start_node = match &args.from {
+        Some(from) => from.clone(),
+        None => get_user_input("Enter the start location: "),
+    };
+    let end_node = match &args.to {
+        Some(to) => to.clone(),
+        None => get_user_input("Enter the end location: "),
+    };
+
+    // Calculate the shortest route from the start node to the end node
+    let (start, end) = match (network.lookup(&start_node), network.lookup(&end_node)) {
+        (Ok(start), Ok(end)) => (start, end),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{}.", e);
+            return Ok(exit_code(&e));
+        }
+    };
+    // Find alternative or constrained routes with Yen's algorithm
+    let lookup_all = |names: &[String]| {
+        names
+            .iter()
+            .map(|name| network.lookup(name))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let constraints = match (lookup_all(&args.avoid), lookup_all(&args.via)) {
+        (Ok(avoid), Ok(via)) => RouteConstraints {
+            avoid,
+            via,
+            max_distance_km: args.max_distance,
+        },
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{}.", e);
+            return Ok(exit_code(&e));
+        }
+    };
+    if alternatives {
+        let routes = network.k_shortest_routes(start, end, args.alternatives, &constraints);
+        if routes.is_empty() {
+            eprintln!(
+                "No route found from {} to {} that satisfies the constraints.",
+                start_node, end_node
+            );
+            return Ok(ExitCode::from(EXIT_UNREACHABLE));
+        }
+        for (i, route) in routes.iter().enumerate() {
+            println!("Route {}: {} km", i + 1, route.distance_km);
+            print_legs(&network, route);
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let algorithms: &[Algorithm] = match args.algorithm {
+        SearchMode::Dijkstra => &[Algorithm::Dijkstra],
+        SearchMode::Astar => &[Algorithm::AStar],
+        SearchMode::Compare => &[Algorithm::Dijkstra, Algorithm::AStar],
+    };
+
+    let scale = network.heuristic_scale();
+    if algorithms.contains(&Algorithm::AStar) && scale < 1.0 {
+        eprintln!(
+            "Warning: some road is shorter than the straight line between its ends, so the \
+             A* estimate is scaled down by {:.3} and A* may expand as many locations as Dijkstra.",
+            scale
+        );
+    }
+
+    let mut distances = Vec::new();
+    for &algorithm in algorithms {
+        let Some(result) = network.search(start, end, algorithm) else {
+            let e = RouteError::Unreachable {
+                from: start_node,
+                to: end_node,
+            };
+            eprintln!("{}.", e);
+            return Ok(exit_code(&e));
+        };
+
+        // Print the route once, then only the statistics of the other algorithm
+        if distances.is_empty() {
+            print_route(&network, &result.route);
+        }
+        if algorithms.len() > 1 {
             println!(
-                "The shortest distance from {} to {} is {} km",
-                start_node, end_node, distance
+                "{} expanded {} locations and found a route of {} km",
+                algorithm, result.expanded, result.route.distance_km
             );
-        } else {
-            println!("No route found from {} to {}.", start_node, end_node);
         }
-    } else {
-        println!("One or both of the specified nodes do not exist in the graph.");
+        distances.push(result.route.distance_km);
+    }
+
+    // Check that every algorithm found a route of the same length, allowing for
+    // rounding when the same legs are summed in a different order
+    if distances
+        .windows(2)
+        .any(|pair| (pair[0] - pair[1]).abs() > 1e-9 * pair[0].max(pair[1]).max(1.0))
+    {
+        eprintln!(
+            "Dijkstra and A* disagree on the shortest distance: {:?} km.",
+            distances
+        );
+        return Ok(ExitCode::from(EXIT_MISMATCH));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Routes every pair in the batch file and writes the results as CSV
+fn run_batch_file(
+    network: &RoadNetwork,
+    path: &PathBuf,
+    output: Option<&PathBuf>,
+) -> Result<ExitCode, Box<dyn Error>> {
+    let input = File::open(path)?;
+    let summary = match output {
+        Some(output) => run_batch(network, input, BufWriter::new(File::create(output)?))?,
+        None => run_batch(network, input, io::stdout().lock())?,
+    };
+
+    eprintln!(
+        "{} pairs routed, {} with unknown locations, {} unreachable",
+        summary.routed, summary.unknown_location, summary.unreachable
+    );
+
+    Ok(match summary.worst_status() {
+        RouteStatus::UnknownLocation => ExitCode::from(EXIT_UNKNOWN_LOCATION),
+        RouteStatus::Unreachable => ExitCode::from(EXIT_UNREACHABLE),
+        RouteStatus::Ok => ExitCode::SUCCESS,
+    })
+}
+
+/// Plans and prints the shortest round trip from --from through the --tour stops
+fn run_tour(network: &RoadNetwork, args: &Args) -> Result<ExitCode, Box<dyn Error>> {
+    let start_node = args.from.as_deref().ok_or("--tour requires --from")?;
+    let places = std::iter::once(start_node)
+        .chain(args.tour.iter().map(String::as_str))
+        .map(|name| network.lookup(name))
+        .collect::<Result<Vec<_>, _>>();
+    let tour = match places.and_then(|places| network.plan_tour(places[0], &places[1..])) {
+        Ok(tour) => tour,
+        Err(e) => {
+            eprintln!("{}.", e);
+            return Ok(exit_code(&e));
+        }
+    };
+
+    println!(
+        "Tour from {} visiting {} stops ({}):",
+        start_node,
+        tour.stops.len() - 2,
+        tour.method
+    );
+    for (pair, leg) in tour.stops.windows(2).zip(&tour.legs) {
+        println!(
+            "{} -> {}: {} km",
+            network.name(pair[0]),
+            network.name(pair[1]),
+            leg
+        );
+    }
+    println!("The total distance of the tour is {} km", tour.distance_km);
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Maps a routing failure to the process exit code
+fn exit_code(error: &RouteError) -> ExitCode {
+    match error {
+        RouteError::UnknownLocation(_) => ExitCode::from(EXIT_UNKNOWN_LOCATION),
+        RouteError::Unreachable { .. } => ExitCode::from(EXIT_UNREACHABLE),
+    }
+}
+
+/// Prints each leg of a route followed by the total distance
+fn print_route(network: &RoadNetwork, route: &Route) {
+    print_legs(network, route);
+    println!(
+        "The shortest distance from {} to {} is {} km",
+        network.name(route.nodes[0]),
+        network.name(route.nodes[route.nodes.len() - 1]),
+        route.distance_km
+    );
+}
+
+/// Prints each leg of a route
+fn print_legs(network: &RoadNetwork, route: &Route) {
+    for (pair, leg) in route.nodes.windows(2).zip(&route.legs) {
+        println!(
+            "{} -> {}: {} km",
+            network.name(pair[0]),
+            network.name(pair[1]),
+            leg
+        );
     }
 }
 
@@ -99,3 +432,16 @@ fn get_user_input(prompt: &str) -> String {
 // } else {
 //     println!("No route found from Belem Tower to Lisbon Cathedral.");
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_distance_range() {
+        assert_eq!(parse_max_distance("12.5"), Ok(12.5));
+        for invalid in ["0", "-3", "NaN", "inf", "far"] {
+            assert!(parse_max_distance(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
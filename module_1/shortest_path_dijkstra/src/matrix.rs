@@ -0,0 +1,183 @@
+/* All-pairs shortest distances between every two locations in a road network.
+
+Small networks use the Floyd–Warshall algorithm, which relaxes every pair of
+locations through every intermediate location in O(n³) time with very little
+overhead. Larger networks run Dijkstra's algorithm once from every location,
+in parallel with rayon; on sparse road networks this is much faster than O(n³).
+
+The matrix is written as CSV with the location names as headers, one row per
+start location and an empty cell where a location is unreachable:
+```text
+location,Belem Tower,Jerónimos Monastery,...
+Belem Tower,0,1,...
+```
+*/
+
+use crate::RoadNetwork;
+use petgraph::algo::dijkstra;
+use petgraph::prelude::*;
+use rayon::prelude::*;
+use std::error::Error;
+use std::io::Write;
+
+/// Networks with at most this many locations use Floyd–Warshall; larger ones use
+/// parallel Dijkstra.
+pub const FLOYD_WARSHALL_MAX_NODES: usize = 200;
+
+/// The shortest distance in km between every pair of locations, indexed like the
+/// network's nodes. `None` marks a location that cannot be reached.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceMatrix {
+    pub names: Vec<String>,
+    pub distances: Vec<Vec<Option<f64>>>,
+}
+
+impl DistanceMatrix {
+    /// Returns the distance from one location to another, if it can be reached.
+    pub fn get(&self, from: NodeIndex, to: NodeIndex) -> Option<f64> {
+        self.distances[from.index()][to.index()]
+    }
+
+    /// Writes the matrix as CSV with the location names as headers.
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<(), Box<dyn Error>> {
+        let mut wtr = csv::Writer::from_writer(writer);
+
+        let mut header = vec!["location"];
+        header.extend(self.names.iter().map(String::as_str));
+        wtr.write_record(&header)?;
+
+        for (name, row) in self.names.iter().zip(&self.distances) {
+            let mut record = vec![name.clone()];
+            record.extend(
+                row.iter()
+                    .map(|distance| distance.map(|d| d.to_string()).unwrap_or_default()),
+            );
+            wtr.write_record(&record)?;
+        }
+
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+impl RoadNetwork {
+    /// Computes the distance matrix, choosing Floyd–Warshall or parallel Dijkstra by
+    /// the size of the network.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shortest_path_dijkstra::RoadNetwork;
+    ///
+    /// let network = RoadNetwork::lisbon();
+    /// let matrix = network.distance_matrix();
+    /// let belem = network.node("Belem Tower").unwrap();
+    /// let cathedral = network.node("Lisbon Cathedral").unwrap();
+    /// assert_eq!(matrix.get(belem, cathedral), Some(8.5));
+    /// ```
+    pub fn distance_matrix(&self) -> DistanceMatrix {
+        if self.graph.node_count() <= FLOYD_WARSHALL_MAX_NODES {
+            self.floyd_warshall_matrix()
+        } else {
+            self.parallel_dijkstra_matrix()
+        }
+    }
+
+    /// Computes the distance matrix with the Floyd–Warshall algorithm.
+    pub fn floyd_warshall_matrix(&self) -> DistanceMatrix {
+        let n = self.graph.node_count();
+        let mut dist = vec![vec![f64::INFINITY; n]; n];
+
+        // Starts from the direct roads, keeping the shortest of any parallel roads.
+        for (i, row) in dist.iter_mut().enumerate() {
+            row[i] = 0.0;
+        }
+        for edge in self.graph.edge_references() {
+            let (a, b) = (edge.source().index(), edge.target().index());
+            dist[a][b] = dist[a][b].min(*edge.weight());
+            dist[b][a] = dist[a][b];
+        }
+
+        // Allows each location in turn as an intermediate stop.
+        for k in 0..n {
+            let row_k = dist[k].clone();
+            for row in dist.iter_mut() {
+                let through_k = row[k];
+                if through_k == f64::INFINITY {
+                    continue;
+                }
+                for (distance, onwards) in row.iter_mut().zip(&row_k) {
+                    *distance = distance.min(through_k + onwards);
+                }
+            }
+        }
+
+        self.matrix(
+            dist.into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|d| d.is_finite().then_some(d))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// Computes the distance matrix by running Dijkstra from every location in parallel.
+    pub fn parallel_dijkstra_matrix(&self) -> DistanceMatrix {
+        let n = self.graph.node_count();
+        let distances = (0..n)
+            .into_par_iter()
+            .map(|source| {
+                let reached = dijkstra(&self.graph, NodeIndex::new(source), None, |e| *e.weight());
+                (0..n)
+                    .map(|target| reached.get(&NodeIndex::new(target)).copied())
+                    .collect()
+            })
+            .collect();
+        self.matrix(distances)
+    }
+
+    fn matrix(&self, distances: Vec<Vec<Option<f64>>>) -> DistanceMatrix {
+        DistanceMatrix {
+            names: self.locations().map(str::to_string).collect(),
+            distances,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_floyd_warshall_matches_parallel_dijkstra() {
+        let mut network = RoadNetwork::lisbon();
+        network.add_road("LX Factory", "Commerce Square", 4.0); // a shorter parallel road
+        network.add_road("Porto", "Braga", 55.0); // unreachable from Lisbon
+
+        let floyd_warshall = network.floyd_warshall_matrix();
+        let dijkstra = network.parallel_dijkstra_matrix();
+
+        assert_eq!(floyd_warshall, dijkstra);
+        let lx = network.node("LX Factory").unwrap();
+        let square = network.node("Commerce Square").unwrap();
+        let porto = network.node("Porto").unwrap();
+        assert_eq!(floyd_warshall.get(lx, square), Some(4.0));
+        assert_eq!(floyd_warshall.get(lx, porto), None);
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let mut network = RoadNetwork::new();
+        network.add_road("A", "B", 2.5);
+        network.add_location("C");
+        let mut output = Vec::new();
+        network.distance_matrix().write_csv(&mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "location,A,B,C\nA,0,2.5,\nB,2.5,0,\nC,,,0\n"
+        );
+    }
+}
@@ -0,0 +1,244 @@
+/* Tour planning: the shortest round trip from a start location through a chosen
+set of stops and back (the travelling salesman problem).
+
+The distance between every two stops is first found with Dijkstra's algorithm,
+so the tour may pass through locations that are not stops. Small sets of stops
+are solved exactly with the Held–Karp dynamic programme, which takes O(2ⁿ·n²)
+time. Above `HELD_KARP_MAX_STOPS` the tour is built by always driving to the
+nearest unvisited stop, then improved with 2-opt, which reverses any stretch of
+the tour that makes it shorter until no reversal helps. That is fast but not
+guaranteed to be optimal.
+*/
+
+use crate::{RoadNetwork, RouteError};
+use petgraph::algo::dijkstra;
+use petgraph::prelude::*;
+use std::fmt;
+
+/// The most stops, not counting the start, that are solved exactly with Held–Karp.
+pub const HELD_KARP_MAX_STOPS: usize = 12;
+
+/// How a tour was planned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TourMethod {
+    /// Exact, with the Held–Karp algorithm.
+    HeldKarp,
+    /// Approximate, with nearest neighbour and 2-opt.
+    NearestNeighbourTwoOpt,
+}
+
+impl fmt::Display for TourMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TourMethod::HeldKarp => write!(f, "Held–Karp, exact"),
+            TourMethod::NearestNeighbourTwoOpt => write!(f, "nearest neighbour and 2-opt"),
+        }
+    }
+}
+
+/// A round trip that starts and ends at the same location.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tour {
+    /// The stops in visiting order, beginning and ending with the start.
+    pub stops: Vec<NodeIndex>,
+    /// The shortest distance between each consecutive pair of stops.
+    pub legs: Vec<f64>,
+    /// The total distance of the tour.
+    pub distance_km: f64,
+    pub method: TourMethod,
+}
+
+impl RoadNetwork {
+    /// Plans the shortest tour from `start` through every stop and back to `start`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shortest_path_dijkstra::RoadNetwork;
+    ///
+    /// let network = RoadNetwork::lisbon();
+    /// let start = network.node("Belem Tower").unwrap();
+    /// let stops = ["LX Factory", "Lisbon Cathedral"].map(|name| network.node(name).unwrap());
+    /// let tour = network.plan_tour(start, &stops).unwrap();
+    /// assert_eq!(tour.distance_km, 18.0);
+    /// ```
+    pub fn plan_tour(&self, start: NodeIndex, stops: &[NodeIndex]) -> Result<Tour, RouteError> {
+        // The start is index 0; repeated stops are only visited once.
+        let mut places = vec![start];
+        for &stop in stops {
+            if !places.contains(&stop) {
+                places.push(stop);
+            }
+        }
+
+        // The shortest distance between every pair of places.
+        let mut dist = vec![vec![0.0; places.len()]; places.len()];
+        for (i, &from) in places.iter().enumerate() {
+            let reached = dijkstra(&self.graph, from, None, |e| *e.weight());
+            for (j, &to) in places.iter().enumerate() {
+                dist[i][j] = *reached.get(&to).ok_or_else(|| RouteError::Unreachable {
+                    from: self.name(from).to_string(),
+                    to: self.name(to).to_string(),
+                })?;
+            }
+        }
+
+        let (order, method) = if places.len() - 1 <= HELD_KARP_MAX_STOPS {
+            (held_karp(&dist), TourMethod::HeldKarp)
+        } else {
+            (
+                two_opt(&dist, nearest_neighbour(&dist)),
+                TourMethod::NearestNeighbourTwoOpt,
+            )
+        };
+
+        let legs: Vec<f64> = order
+            .windows(2)
+            .map(|pair| dist[pair[0]][pair[1]])
+            .collect();
+        Ok(Tour {
+            stops: order.iter().map(|&i| places[i]).collect(),
+            distance_km: legs.iter().sum(),
+            legs,
+            method,
+        })
+    }
+}
+
+// Finds the optimal closed tour from place 0 with the Held–Karp algorithm.
+// `cost[mask][j]` is the shortest path from 0 through exactly the places in `mask`
+// (bit j - 1 for place j), ending at j.
+fn held_karp(dist: &[Vec<f64>]) -> Vec<usize> {
+    let n = dist.len() - 1;
+    if n == 0 {
+        return vec![0, 0];
+    }
+    let full = (1 << n) - 1;
+    let mut cost = vec![vec![f64::INFINITY; n + 1]; 1 << n];
+    let mut parent = vec![vec![0; n + 1]; 1 << n];
+
+    for j in 1..=n {
+        cost[1 << (j - 1)][j] = dist[0][j];
+    }
+    for mask in 1..=full {
+        for j in 1..=n {
+            if mask & (1 << (j - 1)) == 0 || cost[mask][j] == f64::INFINITY {
+                continue;
+            }
+            for k in 1..=n {
+                if mask & (1 << (k - 1)) != 0 {
+                    continue;
+                }
+                let next = mask | (1 << (k - 1));
+                let candidate = cost[mask][j] + dist[j][k];
+                if candidate < cost[next][k] {
+                    cost[next][k] = candidate;
+                    parent[next][k] = j;
+                }
+            }
+        }
+    }
+
+    // Closes the tour from the best last place, then walks the parents back.
+    let mut last = (1..=n)
+        .min_by(|&a, &b| (cost[full][a] + dist[a][0]).total_cmp(&(cost[full][b] + dist[b][0])))
+        .unwrap();
+    let mut order = vec![0];
+    let mut mask = full;
+    while last != 0 {
+        order.push(last);
+        let previous = parent[mask][last];
+        mask &= !(1 << (last - 1));
+        last = previous;
+    }
+    order.push(0);
+    order.reverse();
+    order
+}
+
+// Builds a closed tour from place 0 by always going to the nearest unvisited place.
+fn nearest_neighbour(dist: &[Vec<f64>]) -> Vec<usize> {
+    let mut visited = vec![false; dist.len()];
+    visited[0] = true;
+    let mut order = vec![0];
+
+    while order.len() < dist.len() {
+        let current = order[order.len() - 1];
+        let next = (0..dist.len())
+            .filter(|&place| !visited[place])
+            .min_by(|&a, &b| dist[current][a].total_cmp(&dist[current][b]))
+            .unwrap();
+        visited[next] = true;
+        order.push(next);
+    }
+
+    order.push(0);
+    order
+}
+
+// Improves a closed tour by reversing stretches of it while that makes it shorter.
+fn two_opt(dist: &[Vec<f64>], mut order: Vec<usize>) -> Vec<usize> {
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 1..order.len() - 2 {
+            for j in i + 1..order.len() - 1 {
+                let (a, b, c, d) = (order[i - 1], order[i], order[j], order[j + 1]);
+                let change = dist[a][c] + dist[b][d] - dist[a][b] - dist[c][d];
+                if change < -1e-9 {
+                    order[i..=j].reverse();
+                    improved = true;
+                }
+            }
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Places on a line at the given positions, so the best tour is end to end and back.
+    fn line(positions: &[f64]) -> Vec<Vec<f64>> {
+        positions
+            .iter()
+            .map(|a| positions.iter().map(|b| (a - b).abs()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_held_karp_finds_the_optimal_tour() {
+        let dist = line(&[0.0, 5.0, 1.0, 3.0]);
+        let order = held_karp(&dist);
+
+        let total: f64 = order.windows(2).map(|p| dist[p[0]][p[1]]).sum();
+        assert_eq!(total, 10.0);
+        assert_eq!(order.first(), Some(&0));
+        assert_eq!(order.last(), Some(&0));
+        assert_eq!(order.len(), 5);
+    }
+
+    #[test]
+    fn test_two_opt_matches_held_karp_on_a_line() {
+        let dist = line(&[0.0, 9.0, 2.0, 7.0, 4.0, 1.0, 8.0]);
+        let tour_length =
+            |order: &[usize]| -> f64 { order.windows(2).map(|p| dist[p[0]][p[1]]).sum() };
+
+        let approximate = two_opt(&dist, nearest_neighbour(&dist));
+        assert_eq!(tour_length(&approximate), tour_length(&held_karp(&dist)));
+    }
+
+    #[test]
+    fn test_plan_tour_reports_unreachable_stops() {
+        let mut network = RoadNetwork::lisbon();
+        network.add_road("Porto", "Braga", 55.0);
+        let start = network.node("Belem Tower").unwrap();
+        let porto = network.node("Porto").unwrap();
+
+        assert!(matches!(
+            network.plan_tour(start, &[porto]),
+            Err(RouteError::Unreachable { .. })
+        ));
+    }
+}